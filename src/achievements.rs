@@ -0,0 +1,405 @@
+use crate::despawn_screen;
+use crate::game::{GameEvent, GameplaySet, TotalTime};
+use crate::input::{Action, Actions};
+use crate::locale::{Strings, FONT};
+use crate::npc::{NpcRole, OfferingZone};
+use crate::storage::Storage;
+use crate::AppState;
+use bevy::ecs::system::SystemParam;
+use bevy::{prelude::*, render::view::RenderLayers};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+const DEFAULT_ACHIEVEMENTS_PATH: &str = "achievements.ron";
+const TOAST_SECONDS: f32 = 4.0;
+
+/// Drops that have to be offered at once for `Achievement::BigOffering`.
+const BIG_OFFERING_DROPS: u32 = 20;
+/// Round time within which a shrine's offering zone has to be reached for
+/// `Achievement::QuickClimb`.
+const QUICK_CLIMB_SECONDS: f32 = 30.0;
+/// Shortest game that counts for `Achievement::NoRiver`.
+const NO_RIVER_SECONDS: f32 = 300.0;
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
+pub enum Achievement {
+    FirstOffering,
+    BigOffering,
+    QuickClimb,
+    GustCatch,
+    NoRiver,
+}
+
+impl Achievement {
+    pub const ALL: [Achievement; 5] = [
+        Achievement::FirstOffering,
+        Achievement::BigOffering,
+        Achievement::QuickClimb,
+        Achievement::GustCatch,
+        Achievement::NoRiver,
+    ];
+
+    /// String ID of the name, the description is under the same ID with `.description`.
+    pub fn id(&self) -> &'static str {
+        match self {
+            Achievement::FirstOffering => "achievement.first_offering",
+            Achievement::BigOffering => "achievement.big_offering",
+            Achievement::QuickClimb => "achievement.quick_climb",
+            Achievement::GustCatch => "achievement.gust_catch",
+            Achievement::NoRiver => "achievement.no_river",
+        }
+    }
+}
+
+/// Achievements unlocked in any game so far.
+#[derive(Resource, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Achievements {
+    pub unlocked: BTreeSet<Achievement>,
+}
+
+impl Achievements {
+    pub fn save(&self, backend: &AchievementBackend) {
+        let result = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| e.to_string())
+            .and_then(|s| backend.0.save(&s));
+        match result {
+            Ok(_) => info!("Saved achievements"),
+            Err(e) => error!("Could not save achievements: {}", e),
+        }
+    }
+}
+
+/// Where achievements are kept, `achievements.ron` unless `--achievements <path>` is given.
+#[derive(Resource)]
+pub struct AchievementBackend(pub Box<dyn Storage>);
+
+impl Default for AchievementBackend {
+    #[cfg(not(target_arch = "wasm32"))]
+    fn default() -> Self {
+        AchievementBackend(Box::new(crate::storage::FileStorage {
+            path: crate::launch_arg("--achievements")
+                .unwrap_or_else(|| String::from(DEFAULT_ACHIEVEMENTS_PATH)),
+        }))
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn default() -> Self {
+        AchievementBackend(Box::new(crate::storage::MemoryStorage::default()))
+    }
+}
+
+/// What the achievements that span a whole round have seen of it so far.
+#[derive(Resource, Default)]
+struct RoundProgress {
+    seconds: f32,
+    used_cleanser: bool,
+}
+
+/// Shown over everything for a few seconds when an achievement unlocks.
+#[derive(Component)]
+pub struct AchievementToast(Timer);
+
+#[derive(Component)]
+pub struct OnAchievementsScreen;
+
+pub struct AchievementsPlugin;
+
+impl Plugin for AchievementsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AchievementBackend>()
+            .init_resource::<Achievements>()
+            .init_resource::<RoundProgress>()
+            .add_systems(PreStartup, load_achievements)
+            // The last events of a round are sent as it ends, so this runs in every state
+            .add_systems(
+                Update,
+                (achievement_system.after(GameplaySet), toast_system),
+            )
+            .add_systems(
+                Update,
+                quick_climb_system
+                    .in_set(GameplaySet)
+                    .run_if(in_state(AppState::Game)),
+            )
+            .add_systems(OnEnter(AppState::Achievements), achievements_setup)
+            .add_systems(
+                Update,
+                achievements_screen_system.run_if(in_state(AppState::Achievements)),
+            )
+            .add_systems(
+                OnExit(AppState::Achievements),
+                despawn_screen::<OnAchievementsScreen>,
+            );
+    }
+}
+
+fn load_achievements(backend: Res<AchievementBackend>, mut achievements: ResMut<Achievements>) {
+    match backend.0.load() {
+        Ok(Some(contents)) => match ron::de::from_str::<Achievements>(&contents) {
+            Ok(a) => {
+                info!("Loaded achievements");
+                *achievements = a;
+            }
+            Err(e) => error!("Could not parse achievements: {}", e),
+        },
+        Ok(None) => {}
+        Err(e) => error!("Could not read achievements: {}", e),
+    }
+}
+
+/// Everything needed to unlock achievements during a round.
+#[derive(SystemParam)]
+struct Unlocker<'w, 's> {
+    commands: Commands<'w, 's>,
+    asset_server: Res<'w, AssetServer>,
+    strings: Res<'w, Strings>,
+    backend: Res<'w, AchievementBackend>,
+    achievements: ResMut<'w, Achievements>,
+}
+
+impl Unlocker<'_, '_> {
+    /// Records each of `unlocked` and shows a toast the first time it is unlocked, then saves
+    /// if anything was new.
+    fn unlock(&mut self, unlocked: impl IntoIterator<Item = Achievement>) {
+        let mut changed = false;
+        for achievement in unlocked {
+            if !self.achievements.unlocked.insert(achievement) {
+                continue;
+            }
+            info!("Unlocked {:?}", achievement);
+            self.commands
+                .spawn((
+                    AchievementToast(Timer::from_seconds(TOAST_SECONDS, TimerMode::Once)),
+                    NodeBundle {
+                        background_color: BackgroundColor(Color::BLACK),
+                        style: Style {
+                            position_type: PositionType::Absolute,
+                            top: Val::Percent(2.0),
+                            left: Val::Percent(35.0),
+                            width: Val::Percent(30.0),
+                            padding: UiRect::all(Val::Px(10.0)),
+                            ..default()
+                        },
+                        z_index: ZIndex::Global(10),
+                        ..default()
+                    },
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        format!(
+                            "{}\n\n{}",
+                            self.strings.get("achievements.unlocked"),
+                            self.strings.get(achievement.id())
+                        ),
+                        TextStyle {
+                            font: self.asset_server.load(FONT),
+                            font_size: 14.0,
+                            color: Color::WHITE,
+                        },
+                    ));
+                });
+            changed = true;
+        }
+        if changed {
+            self.achievements.save(&self.backend);
+        }
+    }
+}
+
+fn achievement_system(
+    mut unlocker: Unlocker,
+    mut progress: ResMut<RoundProgress>,
+    mut game_events: EventReader<GameEvent>,
+) {
+    let mut unlocked = Vec::new();
+    for event in game_events.read() {
+        match event {
+            GameEvent::Start { seconds, .. } => {
+                *progress = RoundProgress {
+                    seconds: *seconds,
+                    used_cleanser: false,
+                };
+            }
+            GameEvent::Offering {
+                role, drops, score, ..
+            } => {
+                if *score > 0 {
+                    unlocked.push(Achievement::FirstOffering);
+                    if *drops >= BIG_OFFERING_DROPS {
+                        unlocked.push(Achievement::BigOffering);
+                    }
+                }
+                if *role == NpcRole::Cleanser && *drops > 0 {
+                    progress.used_cleanser = true;
+                }
+            }
+            GameEvent::DropCollected {
+                riding_gust: true, ..
+            } => unlocked.push(Achievement::GustCatch),
+            GameEvent::GameOver { .. }
+                if progress.seconds >= NO_RIVER_SECONDS && !progress.used_cleanser =>
+            {
+                unlocked.push(Achievement::NoRiver)
+            }
+            _ => {}
+        }
+    }
+
+    unlocker.unlock(unlocked);
+}
+
+/// Unlocks `Achievement::QuickClimb` when anyone gets into a shrine's offering zone early enough.
+fn quick_climb_system(
+    mut unlocker: Unlocker,
+    total_time: Res<TotalTime>,
+    zone_query: Query<(&OfferingZone, &Parent)>,
+    role_query: Query<&NpcRole>,
+) {
+    if unlocker
+        .achievements
+        .unlocked
+        .contains(&Achievement::QuickClimb)
+        || total_time.elapsed_secs() >= QUICK_CLIMB_SECONDS
+    {
+        return;
+    }
+    let reached = zone_query.iter().any(|(zone, parent)| {
+        !zone.players.is_empty()
+            && matches!(role_query.get(parent.get()), Ok(NpcRole::Shrine { .. }))
+    });
+    if reached {
+        unlocker.unlock([Achievement::QuickClimb]);
+    }
+}
+
+fn toast_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut toast_query: Query<(Entity, &mut AchievementToast)>,
+) {
+    for (entity, mut toast) in toast_query.iter_mut() {
+        if toast.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+#[derive(Component)]
+struct AchievementsCamera;
+
+/// One line per achievement with its description below, unlocked ones marked.
+pub fn achievements_text(strings: &Strings, achievements: &Achievements) -> String {
+    let mut text = format!(
+        "{}\n\n",
+        strings.format(
+            "achievements.heading",
+            &[
+                ("unlocked", &achievements.unlocked.len()),
+                ("total", &Achievement::ALL.len())
+            ],
+        )
+    );
+    for achievement in Achievement::ALL {
+        let marker = if achievements.unlocked.contains(&achievement) {
+            "[X]"
+        } else {
+            "[ ]"
+        };
+        text.push_str(&format!(
+            "{} {}\n    {}\n\n",
+            marker,
+            strings.get(achievement.id()),
+            strings.get(&format!("{}.description", achievement.id()))
+        ));
+    }
+    text
+}
+
+fn achievements_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut bg: ResMut<ClearColor>,
+    achievements: Res<Achievements>,
+    strings: Res<Strings>,
+) {
+    bg.0 = Color::srgb(0.7, 0.7, 0.7);
+
+    commands.spawn((
+        OnAchievementsScreen,
+        AchievementsCamera,
+        Camera2dBundle {
+            camera: Camera {
+                order: 1,
+                ..default()
+            },
+            ..default()
+        },
+        RenderLayers::from_layers(&[2, 3]),
+    ));
+
+    commands.spawn((
+        OnAchievementsScreen,
+        RenderLayers::layer(2),
+        TextBundle::from_section(
+            achievements_text(&strings, &achievements),
+            TextStyle {
+                font: asset_server.load(FONT),
+                font_size: 16.0,
+                color: Color::BLACK,
+            },
+        )
+        .with_text_justify(JustifyText::Left)
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Percent(8.0),
+            left: Val::Percent(15.0),
+            ..default()
+        }),
+    ));
+}
+
+fn achievements_screen_system(mut app_state: ResMut<NextState<AppState>>, actions: Res<Actions>) {
+    if actions.any_just_pressed(Action::Confirm) || actions.any_just_pressed(Action::Pause) {
+        app_state.set(AppState::Menu);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::headless::{headless_app, offer_at_tlaloc, start_game};
+
+    #[test]
+    fn big_offering_unlocks_and_saves_achievements() {
+        let mut app = headless_app();
+        start_game(&mut app);
+
+        offer_at_tlaloc(&mut app, 20);
+
+        // Getting up there within the first seconds counts as well
+        let expected = [
+            Achievement::FirstOffering,
+            Achievement::BigOffering,
+            Achievement::QuickClimb,
+        ];
+        let achievements = app.world().resource::<Achievements>();
+        assert_eq!(
+            achievements.unlocked.iter().copied().collect::<Vec<_>>(),
+            expected
+        );
+        let saved = app
+            .world()
+            .resource::<AchievementBackend>()
+            .0
+            .load()
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            ron::de::from_str::<Achievements>(&saved).unwrap(),
+            *achievements
+        );
+        let mut toasts = app.world_mut().query::<&AchievementToast>();
+        assert_eq!(toasts.iter(app.world()).count(), 3);
+    }
+}
@@ -0,0 +1,480 @@
+use crate::game::{
+    ActivePlayer, Difficulty, DropThrough, Game, GameEvent, GameplaySet, Platform, PlayMode,
+    Player, PlayerMovement, WaterCollectable, WaterCollection,
+};
+use crate::input::{Action, Actions, PlayerControls};
+use crate::locale::{Strings, FONT};
+use crate::movement::MovementModel;
+use crate::stats::{RoundStats, GROUND_HEIGHT};
+use crate::PLAYER_MOVEMENT_SPEED;
+use crate::{AppState, InGame};
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use std::collections::VecDeque;
+
+/// Half the height of a character, from its feet to its center.
+const STANDING_OFFSET: f32 = 25.0;
+/// Half the width of the level, the ground spans all of it.
+const GROUND_HALF_WIDTH: f32 = 599.0;
+/// How far a character can be from a ledge and still stand or land on it.
+const FOOTING: f32 = 20.0;
+/// Widest gap between two ledges the companion tries to jump or fall across.
+const JUMP_REACH: f32 = 200.0;
+/// How close to a higher ledge the companion has to be before jumping for it.
+const JUMP_START: f32 = 40.0;
+/// Share of a full jump the companion counts on, leaving room for drifting clouds.
+const JUMP_MARGIN: f32 = 0.8;
+/// The companion stops this far from the character it follows.
+const FOLLOW_DISTANCE: f32 = 60.0;
+/// Drops within this distance of the companion are worth a detour.
+const PICKUP_DISTANCE: f32 = 300.0;
+/// Close enough to a drop to stop walking and let the collider pick it up.
+const ARRIVE_DISTANCE: f32 = 8.0;
+
+/// What the companion was last told, toggled with `Action::Order`.
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum CompanionOrder {
+    /// Come to the active character, collecting drops on the way
+    #[default]
+    Follow,
+    /// Stay where it is
+    Wait,
+}
+
+impl CompanionOrder {
+    /// String ID of the HUD line naming the order
+    fn label(&self) -> &'static str {
+        match self {
+            CompanionOrder::Follow => "companion.follow",
+            CompanionOrder::Wait => "companion.wait",
+        }
+    }
+
+    fn toggled(&self) -> CompanionOrder {
+        match self {
+            CompanionOrder::Follow => CompanionOrder::Wait,
+            CompanionOrder::Wait => CompanionOrder::Follow,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct CompanionOrderLabel;
+
+/// Somewhere a character can stand: a `Platform` or the ground.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ledge {
+    pub left: f32,
+    pub right: f32,
+    /// Height of the center of a character standing on it
+    pub height: f32,
+}
+
+impl Ledge {
+    fn ground() -> Ledge {
+        Ledge {
+            left: -GROUND_HALF_WIDTH,
+            right: GROUND_HALF_WIDTH,
+            height: GROUND_HEIGHT,
+        }
+    }
+
+    fn from_platform(position: Vec2, half_extents: Vec2) -> Ledge {
+        Ledge {
+            left: position.x - half_extents.x,
+            right: position.x + half_extents.x,
+            height: position.y + half_extents.y + STANDING_OFFSET,
+        }
+    }
+
+    /// Horizontal distance from `x` to the ledge, 0 above or below it.
+    fn distance(&self, x: f32) -> f32 {
+        (self.left - x).max(x - self.right).max(0.0)
+    }
+
+    fn gap(&self, other: &Ledge) -> f32 {
+        (other.left - self.right)
+            .max(self.left - other.right)
+            .max(0.0)
+    }
+
+    /// The point on the ledge closest to `x`, kept off the edges when the ledge is wide enough.
+    fn landing(&self, x: f32) -> f32 {
+        let margin = ((self.right - self.left) / 2.0).min(FOOTING);
+        x.clamp(self.left + margin, self.right - margin)
+    }
+}
+
+/// Whether a character on `from` can get onto `to` with one jump or fall.
+fn reachable(from: &Ledge, to: &Ledge, jump_height: f32) -> bool {
+    to.height - from.height <= jump_height && from.gap(to) <= JUMP_REACH
+}
+
+/// The highest ledge at or below `position`, the one a character there stands on or falls to.
+pub fn ledge_under(ledges: &[Ledge], position: Vec2) -> Option<usize> {
+    ledges
+        .iter()
+        .enumerate()
+        .filter(|(_, l)| l.distance(position.x) <= FOOTING && l.height <= position.y + 5.0)
+        .max_by(|(_, a), (_, b)| a.height.total_cmp(&b.height))
+        .map(|(index, _)| index)
+}
+
+/// The ledge to head for next on the way from `start` to `goal`, found breadth-first so the
+/// companion takes as few jumps as it can. `None` when `goal` is out of reach.
+pub fn next_ledge(ledges: &[Ledge], start: usize, goal: usize, jump_height: f32) -> Option<usize> {
+    let mut came_from = vec![None; ledges.len()];
+    came_from[start] = Some(start);
+    let mut queue = VecDeque::from([start]);
+    while let Some(current) = queue.pop_front() {
+        if current == goal {
+            // Walk back to the ledge right after `start`
+            let mut step = goal;
+            while let Some(previous) = came_from[step].filter(|p| *p != start) {
+                step = previous;
+            }
+            return Some(step);
+        }
+        for next in 0..ledges.len() {
+            if came_from[next].is_none() && reachable(&ledges[current], &ledges[next], jump_height)
+            {
+                came_from[next] = Some(current);
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+pub struct CompanionPlugin;
+
+impl Plugin for CompanionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CompanionOrder>()
+            .add_systems(OnEnter(InGame), setup_companion)
+            // Before the world advances, like the input of the active character
+            .add_systems(
+                Update,
+                (
+                    companion_order_system,
+                    companion_system,
+                    companion_order_label_system,
+                )
+                    .chain()
+                    .before(GameplaySet)
+                    .run_if(in_state(AppState::Game))
+                    .run_if(resource_equals(PlayMode::Companion)),
+            );
+    }
+}
+
+fn setup_companion(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    play_mode: Res<PlayMode>,
+    mut order: ResMut<CompanionOrder>,
+) {
+    *order = CompanionOrder::default();
+    if *play_mode != PlayMode::Companion {
+        return;
+    }
+    commands.spawn((
+        Game,
+        CompanionOrderLabel,
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font: asset_server.load(FONT),
+                font_size: 12.0,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Percent(10.0),
+            right: Val::Percent(1.5),
+            ..default()
+        }),
+    ));
+}
+
+/// The active character tells the companion to wait or to come along.
+fn companion_order_system(
+    actions: Res<Actions>,
+    mut order: ResMut<CompanionOrder>,
+    leader_query: Query<&PlayerControls, With<ActivePlayer>>,
+) {
+    if leader_query
+        .iter()
+        .any(|controls| controls.just_pressed(&actions, Action::Order))
+    {
+        *order = order.toggled();
+    }
+}
+
+/// Characters without `ActivePlayer`, the ones `companion_system` drives.
+type CompanionQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static Player,
+        &'static Transform,
+        &'static mut Sprite,
+        &'static mut PlayerMovement,
+        Option<&'static KinematicCharacterControllerOutput>,
+    ),
+    Without<ActivePlayer>,
+>;
+
+/// What the companion looks at before moving: who it follows, where it can stand and what it
+/// can pick up.
+#[derive(SystemParam)]
+struct Surroundings<'w, 's> {
+    water_collection: Res<'w, WaterCollection>,
+    leader: Query<'w, 's, &'static Transform, With<ActivePlayer>>,
+    platforms: Query<'w, 's, (Entity, &'static GlobalTransform, &'static Collider), With<Platform>>,
+    drops: Query<'w, 's, &'static Transform, With<WaterCollectable>>,
+}
+
+impl Surroundings<'_, '_> {
+    fn water(&self, player: &Player) -> u32 {
+        if player.0 == 1 {
+            self.water_collection.total_player1
+        } else {
+            self.water_collection.total_player2
+        }
+    }
+}
+
+/// Counts a companion jump the same way as one made by hand.
+#[derive(SystemParam)]
+struct JumpRecord<'w> {
+    stats: ResMut<'w, RoundStats>,
+    game_events: EventWriter<'w, GameEvent>,
+}
+
+impl JumpRecord<'_> {
+    fn jumped(&mut self, player: &Player, position: Vec2, water: u32) {
+        self.stats.player(player).jumps += 1;
+        self.game_events.send(GameEvent::Jump {
+            player: player.0,
+            position,
+            water,
+        });
+    }
+}
+
+/// Drives the character without `ActivePlayer`: towards drops nearby, otherwise towards the
+/// active character, hopping from ledge to ledge along `next_ledge`.
+fn companion_system(
+    mut commands: Commands,
+    order: Res<CompanionOrder>,
+    movement_model: Res<MovementModel>,
+    difficulty: Res<Difficulty>,
+    surroundings: Surroundings,
+    mut jumps: JumpRecord,
+    mut companion_query: CompanionQuery,
+) {
+    let leader = match surroundings.leader.get_single() {
+        Ok(t) => t.translation.truncate(),
+        Err(_) => return,
+    };
+    let model = MovementModel {
+        weight_penalty: difficulty.weight_penalty(),
+        ..*movement_model
+    };
+
+    // The ground first, then every platform where it is right now
+    let mut ledges = vec![Ledge::ground()];
+    let mut platforms = vec![None];
+    for (entity, transform, collider) in surroundings.platforms.iter() {
+        if let Some(cuboid) = collider.as_cuboid() {
+            ledges.push(Ledge::from_platform(
+                transform.translation().truncate(),
+                cuboid.half_extents(),
+            ));
+            platforms.push(Some(entity));
+        }
+    }
+
+    for (entity, player, transform, mut sprite, mut movement, output) in companion_query.iter_mut()
+    {
+        let position = transform.translation.truncate();
+        let grounded = output.is_some_and(|o| o.grounded);
+        let water = surroundings.water(player);
+        let jump_height = model.jump_height(water) * JUMP_MARGIN;
+        // Drops only count when a jump from the ledge below them reaches them
+        let in_reach = |drop: Vec2| {
+            ledge_under(&ledges, drop).is_some_and(|l| drop.y - ledges[l].height <= jump_height)
+        };
+
+        let target = match *order {
+            CompanionOrder::Wait => None,
+            CompanionOrder::Follow => surroundings
+                .drops
+                .iter()
+                .map(|t| t.translation.truncate())
+                .filter(|drop| drop.distance(position) < PICKUP_DISTANCE && in_reach(*drop))
+                .min_by(|a, b| a.distance(position).total_cmp(&b.distance(position)))
+                .map(|drop| (drop, ARRIVE_DISTANCE))
+                .or(Some((leader, FOLLOW_DISTANCE))),
+        };
+
+        let mut aim = None;
+        let mut jump = false;
+        if let Some((goal, stop_distance)) = target {
+            let here = ledge_under(&ledges, position);
+            let there = ledge_under(&ledges, goal);
+            match (here, there) {
+                (Some(here), Some(there)) if here != there => {
+                    match next_ledge(&ledges, here, there, jump_height) {
+                        Some(next) => {
+                            let ledge = &ledges[next];
+                            aim = Some((ledge.landing(goal.x), 0.0));
+                            if ledge.height > ledges[here].height + 5.0 {
+                                jump = ledge.distance(position.x) <= JUMP_START;
+                            } else if ledge.distance(position.x) == 0.0 && grounded {
+                                // Straight down, through the platform underfoot
+                                if let Some(platform) = platforms[here] {
+                                    commands.entity(entity).insert(DropThrough(platform));
+                                }
+                            }
+                        }
+                        // Out of reach for now, wait underneath
+                        None => aim = Some((goal.x, stop_distance)),
+                    }
+                }
+                _ => {
+                    aim = Some((goal.x, stop_distance));
+                    // Drops hanging above the ledge are caught with a jump
+                    jump = goal.y - position.y > STANDING_OFFSET
+                        && (goal.x - position.x).abs() <= JUMP_START
+                        && stop_distance == ARRIVE_DISTANCE;
+                }
+            }
+        }
+
+        let x_per_second = match aim {
+            Some((x, stop_distance))
+                if (x - position.x).abs() > stop_distance.max(ARRIVE_DISTANCE) =>
+            {
+                (x - position.x).signum() * PLAYER_MOVEMENT_SPEED
+            }
+            _ => 0.0,
+        };
+        if x_per_second < 0.0 {
+            sprite.flip_x = false;
+        } else if x_per_second > 0.0 {
+            sprite.flip_x = true;
+        }
+        movement.walk(x_per_second);
+        // Always full jumps, the ledges were picked for them
+        movement.hold_jump(true);
+
+        if jump && grounded && !movement.is_rising() {
+            // Only the character being played makes a jump sound
+            movement.jump(&model, water);
+            jumps.jumped(player, position, water);
+        }
+    }
+}
+
+fn companion_order_label_system(
+    strings: Res<Strings>,
+    order: Res<CompanionOrder>,
+    companion_query: Query<&Player, Without<ActivePlayer>>,
+    mut label_query: Query<&mut Text, With<CompanionOrderLabel>>,
+) {
+    let name = match companion_query.get_single() {
+        Ok(p) => p.name(),
+        Err(_) => return,
+    };
+    let value = strings.format(order.label(), &[("name", &name)]);
+    for mut text in label_query.iter_mut() {
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{CloudSpawner, WaterCollectableSpawner};
+    use crate::headless::{headless_app, hold, player_x, run, start_game, tap};
+
+    const JUMP_HEIGHT: f32 = 300.0;
+
+    fn ledge(left: f32, right: f32, height: f32) -> Ledge {
+        Ledge {
+            left,
+            right,
+            height,
+        }
+    }
+
+    #[test]
+    fn path_climbs_one_reachable_ledge_at_a_time() {
+        let ledges = [
+            ledge(-600.0, 600.0, 0.0),
+            // Too high to reach from the ground
+            ledge(-100.0, 100.0, 500.0),
+            ledge(200.0, 400.0, 250.0),
+            ledge(-50.0, 50.0, 900.0),
+        ];
+        assert_eq!(next_ledge(&ledges, 0, 1, JUMP_HEIGHT), Some(2));
+        assert_eq!(next_ledge(&ledges, 2, 1, JUMP_HEIGHT), Some(1));
+        assert_eq!(next_ledge(&ledges, 0, 0, JUMP_HEIGHT), Some(0));
+        // Falling works from anywhere close enough
+        assert_eq!(next_ledge(&ledges, 1, 0, JUMP_HEIGHT), Some(0));
+        assert_eq!(next_ledge(&ledges, 0, 3, JUMP_HEIGHT), None);
+    }
+
+    #[test]
+    fn wide_gaps_are_not_jumped() {
+        let ledges = [ledge(-600.0, -400.0, 0.0), ledge(400.0, 600.0, 0.0)];
+        assert_eq!(next_ledge(&ledges, 0, 1, JUMP_HEIGHT), None);
+    }
+
+    #[test]
+    fn stands_on_the_highest_ledge_below() {
+        let ledges = [ledge(-600.0, 600.0, 0.0), ledge(-100.0, 100.0, 200.0)];
+        assert_eq!(ledge_under(&ledges, Vec2::new(0.0, 200.0)), Some(1));
+        assert_eq!(ledge_under(&ledges, Vec2::new(0.0, 150.0)), Some(0));
+        assert_eq!(ledge_under(&ledges, Vec2::new(300.0, 400.0)), Some(0));
+    }
+
+    #[test]
+    fn companion_follows_and_waits_when_told() {
+        let mut app = headless_app();
+        app.insert_resource(PlayMode::Companion);
+        start_game(&mut app);
+        // Nothing drifting by or worth picking up on the way
+        let spawners = app
+            .world_mut()
+            .query_filtered::<Entity, Or<(With<CloudSpawner>, With<WaterCollectableSpawner>)>>()
+            .iter(app.world())
+            .collect::<Vec<_>>();
+        for entity in spawners {
+            app.world_mut().entity_mut(entity).despawn_recursive();
+        }
+        run(&mut app, 300);
+        assert!((player_x(&mut app, 1) - player_x(&mut app, 2)).abs() < 70.0);
+
+        tap(&mut app, KeyCode::KeyQ);
+        assert_eq!(
+            *app.world().resource::<CompanionOrder>(),
+            CompanionOrder::Wait
+        );
+        let waiting = player_x(&mut app, 2);
+        hold(&mut app, KeyCode::KeyA, 60);
+        assert!((player_x(&mut app, 2) - waiting).abs() < 1.0);
+        assert!((player_x(&mut app, 1) - waiting).abs() > 100.0);
+
+        tap(&mut app, KeyCode::KeyQ);
+        run(&mut app, 200);
+        assert!((player_x(&mut app, 1) - player_x(&mut app, 2)).abs() < 70.0);
+    }
+}
@@ -0,0 +1,261 @@
+use crate::despawn_screen;
+use crate::input::{nth_gamepad, Action, Actions, Binding, InputBindings};
+use crate::locale::Strings;
+use crate::AppState;
+use bevy::ecs::system::SystemParam;
+use bevy::{prelude::*, render::view::RenderLayers};
+
+const CAPTURED_AXES: [GamepadAxisType; 4] = [
+    GamepadAxisType::LeftStickX,
+    GamepadAxisType::LeftStickY,
+    GamepadAxisType::RightStickX,
+    GamepadAxisType::RightStickY,
+];
+
+/// How far a stick has to be pushed to be picked up as a new binding.
+const CAPTURE_THRESHOLD: f32 = 0.5;
+
+#[derive(Component)]
+pub struct OnControlsScreen;
+
+#[derive(Component)]
+struct ControlsMenu {
+    rate_limit_selection: Timer,
+    /// One row per action, then "Reset to defaults" and "Back"
+    selection_index: usize,
+    player: usize,
+    /// Waiting for a key, button or stick to bind to the selected action
+    listening: bool,
+}
+
+pub struct ControlsPlugin;
+
+impl Plugin for ControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::Controls), controls_setup)
+            .add_systems(
+                Update,
+                controls_selection_system.run_if(in_state(AppState::Controls)),
+            )
+            .add_systems(
+                OnExit(AppState::Controls),
+                despawn_screen::<OnControlsScreen>,
+            );
+    }
+}
+
+#[derive(Component)]
+struct ControlsCamera;
+
+fn controls_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut bg: ResMut<ClearColor>,
+    bindings: Res<InputBindings>,
+    strings: Res<Strings>,
+) {
+    bg.0 = Color::srgb(0.7, 0.7, 0.7);
+
+    commands.spawn((
+        OnControlsScreen,
+        ControlsCamera,
+        Camera2dBundle {
+            camera: Camera {
+                order: 1,
+                ..default()
+            },
+            ..default()
+        },
+        RenderLayers::from_layers(&[2, 3]),
+    ));
+
+    let menu = ControlsMenu {
+        rate_limit_selection: Timer::from_seconds(0.2, TimerMode::Once),
+        selection_index: 0,
+        player: 0,
+        listening: false,
+    };
+    let style = TextStyle {
+        font: asset_server.load("fonts/PressStart2P-vaV7.ttf"),
+        font_size: 16.0,
+        color: Color::BLACK,
+    };
+    commands.spawn((
+        OnControlsScreen,
+        RenderLayers::layer(2),
+        TextBundle::from_sections(controls_sections(&menu, &bindings, &strings, &style))
+            .with_text_justify(JustifyText::Left)
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                top: Val::Percent(8.0),
+                left: Val::Percent(8.0),
+                ..default()
+            }),
+        menu,
+    ));
+}
+
+fn controls_sections(
+    menu: &ControlsMenu,
+    bindings: &InputBindings,
+    strings: &Strings,
+    style: &TextStyle,
+) -> Vec<TextSection> {
+    let section = |value: String| TextSection {
+        value,
+        style: style.clone(),
+    };
+    let marker = |index: usize| {
+        if index == menu.selection_index {
+            "▶ "
+        } else {
+            "  "
+        }
+    };
+
+    let mut sections = vec![section(format!(
+        "{}\n\n",
+        strings.format("controls.heading", &[("player", &(menu.player + 1))])
+    ))];
+    let player_bindings = &bindings.players[menu.player];
+    for (index, action) in Action::ALL.iter().enumerate() {
+        let value = if menu.listening && index == menu.selection_index {
+            strings.get("controls.listening").to_string()
+        } else {
+            player_bindings
+                .actions
+                .get(action)
+                .map(|b| b.iter().map(Binding::label).collect::<Vec<_>>().join(", "))
+                .unwrap_or_default()
+        };
+        sections.push(section(format!(
+            "{}{}: {}\n\n",
+            marker(index),
+            strings.get(action.label()),
+            value
+        )));
+    }
+    sections.push(section(format!(
+        "{}{}\n\n",
+        marker(Action::ALL.len()),
+        strings.get("controls.reset")
+    )));
+    sections.push(section(format!(
+        "{}{}",
+        marker(Action::ALL.len() + 1),
+        strings.get("common.back")
+    )));
+    sections
+}
+
+/// Raw keyboard and gamepad state, read directly while a new binding is captured.
+#[derive(SystemParam)]
+struct RawInput<'w> {
+    keyboard_input: Res<'w, ButtonInput<KeyCode>>,
+    gamepads: Res<'w, Gamepads>,
+    button_inputs: Res<'w, ButtonInput<GamepadButton>>,
+    axes: Res<'w, Axis<GamepadAxis>>,
+}
+
+impl RawInput<'_> {
+    /// The first key, button or stick the player touched this frame, on the keyboard or the
+    /// `gamepad_index`th gamepad.
+    fn captured_binding(&self, gamepad_index: usize) -> Option<Binding> {
+        if let Some(key) = self.keyboard_input.get_just_pressed().next() {
+            return Some(Binding::Key(*key));
+        }
+        let gamepad = nth_gamepad(&self.gamepads, gamepad_index);
+        if let Some(button) = self
+            .button_inputs
+            .get_just_pressed()
+            .find(|b| b.gamepad == gamepad)
+        {
+            return Some(Binding::Button(button.button_type));
+        }
+        CAPTURED_AXES.iter().find_map(|axis| {
+            let value = self
+                .axes
+                .get(GamepadAxis::new(gamepad, *axis))
+                .unwrap_or(0.0);
+            if value.abs() > CAPTURE_THRESHOLD {
+                Some(Binding::Axis(*axis, value > 0.0))
+            } else {
+                None
+            }
+        })
+    }
+}
+
+fn controls_selection_system(
+    time: Res<Time>,
+    mut app_state: ResMut<NextState<AppState>>,
+    mut bindings: ResMut<InputBindings>,
+    actions: Res<Actions>,
+    raw_input: RawInput,
+    strings: Res<Strings>,
+    mut controls_menu_query: Query<(&mut Text, &mut ControlsMenu)>,
+) {
+    let (mut text, mut menu) = match controls_menu_query.get_single_mut() {
+        Ok(q) => q,
+        Err(_) => return,
+    };
+    menu.rate_limit_selection.tick(time.delta());
+    let mut changed = false;
+
+    if menu.listening {
+        if raw_input.keyboard_input.just_pressed(KeyCode::Escape) {
+            menu.listening = false;
+            changed = true;
+        } else {
+            if let Some(binding) = raw_input.captured_binding(bindings.players[menu.player].gamepad)
+            {
+                let action = Action::ALL[menu.selection_index];
+                let player = menu.player;
+                let action_bindings = bindings.players[player].actions.entry(action).or_default();
+                action_bindings.retain(|b| b.is_gamepad() != binding.is_gamepad());
+                action_bindings.push(binding);
+                menu.listening = false;
+                menu.rate_limit_selection.reset();
+                changed = true;
+            }
+        }
+    } else {
+        let up_key_pressed = actions.any_pressed(Action::NavigateUp);
+        let down_key_pressed = actions.any_pressed(Action::NavigateDown);
+        let left_key_pressed = actions.any_pressed(Action::NavigateLeft);
+        let right_key_pressed = actions.any_pressed(Action::NavigateRight);
+        let last_row = Action::ALL.len() + 1;
+
+        if menu.rate_limit_selection.finished() {
+            if up_key_pressed && menu.selection_index > 0 {
+                menu.selection_index -= 1;
+                menu.rate_limit_selection.reset();
+                changed = true;
+            } else if down_key_pressed && menu.selection_index < last_row {
+                menu.selection_index += 1;
+                menu.rate_limit_selection.reset();
+                changed = true;
+            } else if left_key_pressed || right_key_pressed {
+                menu.player = (menu.player + 1) % bindings.players.len();
+                menu.rate_limit_selection.reset();
+                changed = true;
+            }
+        }
+
+        if actions.any_just_pressed(Action::Confirm) {
+            if menu.selection_index < Action::ALL.len() {
+                menu.listening = true;
+            } else if menu.selection_index == Action::ALL.len() {
+                *bindings = InputBindings::default();
+            } else {
+                app_state.set(AppState::Menu);
+            }
+            changed = true;
+        }
+    }
+
+    if changed {
+        let style = text.sections[0].style.clone();
+        text.sections = controls_sections(&menu, &bindings, &strings, &style);
+    }
+}
@@ -0,0 +1,262 @@
+use bevy::asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext};
+use bevy::prelude::*;
+use serde::Deserialize;
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// A conversation loaded from a `.dialog.ron` file, e.g. `assets/dialogs/tlaloc.dialog.ron`.
+#[derive(Asset, TypePath, Debug, Deserialize)]
+pub struct DialogTree {
+    /// Tried in order when the dialog opens, the first one whose conditions hold picks the node
+    pub start: Vec<DialogRoute>,
+    pub nodes: HashMap<String, DialogNode>,
+    /// Sound played while the text is being revealed
+    #[serde(default)]
+    pub blip: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DialogRoute {
+    #[serde(default)]
+    pub conditions: Vec<DialogCondition>,
+    pub node: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DialogNode {
+    /// String ID from `assets/locale`, plain text is shown as it is
+    pub text: String,
+    /// Lines added below the text when their conditions hold
+    #[serde(default)]
+    pub notes: Vec<DialogNote>,
+    /// Picked with left and right, confirmed with the confirm action
+    #[serde(default)]
+    pub choices: Vec<DialogChoice>,
+    /// Node shown after confirming when there are no choices, the dialog closes when empty
+    #[serde(default)]
+    pub next: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DialogNote {
+    #[serde(default)]
+    pub conditions: Vec<DialogCondition>,
+    /// String ID, `{missing}` is replaced with the names of the characters outside the
+    /// speaker's `OfferingZone`
+    pub text: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DialogChoice {
+    /// String ID
+    pub label: String,
+    /// The choice is hidden unless all of these hold
+    #[serde(default)]
+    pub conditions: Vec<DialogCondition>,
+    /// Applied in order when the choice is confirmed
+    #[serde(default)]
+    pub effects: Vec<DialogEffect>,
+    /// The dialog closes when empty
+    #[serde(default)]
+    pub next: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub enum DialogCondition {
+    /// Either character carries at least one drop
+    HasWater,
+    /// Every character is inside the speaker's `OfferingZone`, always true without one
+    AllInRange,
+    Not(Box<DialogCondition>),
+}
+
+#[derive(Debug, Deserialize)]
+pub enum DialogEffect {
+    /// Hands the water of the characters in range to the speaker, see `NpcRole`
+    Offer,
+    /// Sends the characters back to the ground with a rain animation
+    StartReset,
+    PlaySound(String),
+}
+
+impl DialogTree {
+    /// Every node a route or `next` points to has to exist.
+    fn validate(&self) -> Result<(), DialogLoaderError> {
+        let targets =
+            self.start
+                .iter()
+                .map(|route| &route.node)
+                .chain(self.nodes.values().flat_map(|node| {
+                    node.next
+                        .iter()
+                        .chain(node.choices.iter().filter_map(|c| c.next.as_ref()))
+                }));
+        for target in targets {
+            if !self.nodes.contains_key(target) {
+                return Err(DialogLoaderError::MissingNode(target.clone()));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Word-wraps `text` to `columns` and splits it into pages of at most `rows` lines, keeping
+/// `reserved` lines free on the last page for the choices.
+pub fn paginate(text: &str, columns: usize, rows: usize, reserved: usize) -> Vec<String> {
+    let columns = columns.max(1);
+    let rows = rows.max(1);
+
+    let mut lines = Vec::new();
+    for paragraph in text.split('\n') {
+        let mut line = String::new();
+        let mut width = 0;
+        for word in paragraph.split_whitespace() {
+            let mut chars = word.chars().collect::<Vec<_>>();
+            // Words longer than a line are broken up
+            while chars.len() > columns {
+                if width > 0 {
+                    lines.push(std::mem::take(&mut line));
+                    width = 0;
+                }
+                lines.push(chars.drain(..columns).collect());
+            }
+            if chars.is_empty() {
+                continue;
+            }
+            if width > 0 && width + 1 + chars.len() > columns {
+                lines.push(std::mem::take(&mut line));
+                width = 0;
+            }
+            if width > 0 {
+                line.push(' ');
+                width += 1;
+            }
+            width += chars.len();
+            line.extend(chars);
+        }
+        lines.push(line);
+    }
+
+    let mut pages: Vec<Vec<String>> = Vec::new();
+    for line in lines {
+        match pages.last_mut() {
+            Some(page) if page.len() < rows => page.push(line),
+            // Pages never start with a blank line
+            _ if line.is_empty() => {}
+            _ => pages.push(vec![line]),
+        }
+    }
+    let keep = rows.saturating_sub(reserved);
+    if let Some(last) = pages.last_mut() {
+        if keep > 0 && last.len() > keep {
+            let rest = last
+                .split_off(keep)
+                .into_iter()
+                .skip_while(|line| line.is_empty())
+                .collect::<Vec<_>>();
+            if !rest.is_empty() {
+                pages.push(rest);
+            }
+        }
+    }
+
+    if pages.is_empty() {
+        return vec![String::new()];
+    }
+    pages.into_iter().map(|page| page.join("\n")).collect()
+}
+
+#[derive(Debug, Error)]
+pub enum DialogLoaderError {
+    #[error("Could not read dialog: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not parse dialog: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("Dialog refers to missing node {0:?}")]
+    MissingNode(String),
+}
+
+#[derive(Default)]
+pub struct DialogLoader;
+
+impl AssetLoader for DialogLoader {
+    type Asset = DialogTree;
+    type Settings = ();
+    type Error = DialogLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<DialogTree, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let tree = ron::de::from_bytes::<DialogTree>(&bytes)?;
+        tree.validate()?;
+        Ok(tree)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["dialog.ron"]
+    }
+}
+
+pub struct DialogPlugin;
+
+impl Plugin for DialogPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<DialogTree>()
+            .init_asset_loader::<DialogLoader>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::PlayerDialog;
+    use crate::headless::{active_player, headless_app, start_game, tap, visit_chalchiuhtlicue};
+
+    #[test]
+    fn long_words_are_broken_across_lines() {
+        assert_eq!(
+            paginate("a abcdefghij b", 4, 3, 0),
+            ["a\nabcd\nefgh", "ij b"]
+        );
+    }
+
+    #[test]
+    fn lines_that_fill_the_columns_exactly_are_kept_whole() {
+        assert_eq!(paginate("abc def ghi jkl", 7, 2, 0), ["abc def\nghi jkl"]);
+        // Unless the choices need the room
+        assert_eq!(paginate("abc def ghi jkl", 7, 2, 1), ["abc def", "ghi jkl"]);
+    }
+
+    #[test]
+    fn pages_never_start_with_a_blank_line() {
+        assert_eq!(paginate("a\n\nb", 10, 1, 0), ["a", "b"]);
+    }
+
+    #[test]
+    fn empty_text_is_one_empty_page() {
+        assert_eq!(paginate("", 10, 3, 1), [""]);
+        assert_eq!(paginate("  \n ", 10, 3, 1), [""]);
+    }
+
+    #[test]
+    fn long_dialog_is_read_page_by_page() {
+        let mut app = headless_app();
+        start_game(&mut app);
+
+        let player = active_player(&mut app);
+        visit_chalchiuhtlicue(&mut app);
+
+        // The river goddess' intro takes two pages: open, finish, turn, finish
+        for _ in 0..4 {
+            tap(&mut app, KeyCode::Space);
+            assert!(app.world().get::<PlayerDialog>(player).unwrap().is_open());
+        }
+        tap(&mut app, KeyCode::Space);
+        assert!(!app.world().get::<PlayerDialog>(player).unwrap().is_open());
+    }
+}
@@ -1,19 +1,28 @@
 use crate::camera_tracking;
 use crate::despawn_screen;
+use crate::dialog::{paginate, DialogCondition, DialogEffect, DialogNode, DialogTree};
+use crate::input::{Action, Actions, PlayerControls};
+use crate::locale::Strings;
 use crate::menu::TotalSeconds;
-use crate::AppState;
-use crate::{
-    CHARACTER_DOWNWARD_VELOCITY_PER_FRAME, MAXIMUM_DOWNWARD_VELOCITY, PLAYER_MOVEMENT_SPEED,
-};
+use crate::movement::{Motion, MovementModel};
+use crate::npc::{NpcRole, OfferingZone, OfferingZoneLabel};
+use crate::scoring::{spawn_score_popup, OfferingStreak, ScoreBreakdown};
+use crate::seed::{self, RunSeed};
+use crate::stats::RoundStats;
+use crate::PLAYER_MOVEMENT_SPEED;
+use crate::{AppState, InGame};
 use bevy::audio::PlaybackMode;
+use bevy::ecs::system::SystemParam;
 use bevy::text::TextLayoutInfo;
 use bevy::time::Stopwatch;
+use bevy::window::PrimaryWindow;
 use bevy::{prelude::*, render::texture::ImageLoader};
 use bevy_prng::ChaCha8Rng;
 use bevy_rand::resource::GlobalEntropy;
 use bevy_rapier2d::na::distance;
 use bevy_rapier2d::prelude::*;
 use rand::prelude::Rng;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::ops::Range;
 use std::time::Duration;
@@ -28,6 +37,10 @@ enum GamePhase {
     Reset,
 }
 
+/// Systems that read player input or advance the game world, in that order.
+#[derive(SystemSet, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct GameplaySet;
+
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, Resource)]
 enum DevPhase {
     #[default]
@@ -37,8 +50,8 @@ enum DevPhase {
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, Resource)]
 pub struct WaterCollection {
-    total_player1: u32,
-    total_player2: u32,
+    pub total_player1: u32,
+    pub total_player2: u32,
 }
 
 // #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, Resource)]
@@ -50,6 +63,9 @@ pub struct WaterCollection {
 // #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, Resource)]
 // pub struct Altitude(pub u32);
 
+/// Seconds between chances of a gust on Normal, scaled by `Difficulty::spawn_rate`.
+const GUST_SECONDS: f32 = 1.0;
+
 #[derive(Resource, Deref, DerefMut)]
 pub struct GustTimer(Timer);
 
@@ -59,68 +75,331 @@ pub struct TotalScore(pub u32);
 #[derive(Resource, Deref, DerefMut)]
 pub struct TotalTime(Timer);
 
+/// Something that happened during a round, for systems that only watch the game, e.g. telemetry.
+#[derive(Event, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event")]
+pub enum GameEvent {
+    Start {
+        seed: u64,
+        seconds: f32,
+        play_mode: PlayMode,
+        #[serde(default)]
+        difficulty: Difficulty,
+    },
+    DropSpawned {
+        position: Vec2,
+    },
+    DropCollected {
+        player: usize,
+        position: Vec2,
+        /// The character was standing on a `WindGust`
+        riding_gust: bool,
+    },
+    DropExpired {
+        position: Vec2,
+    },
+    Jump {
+        player: usize,
+        position: Vec2,
+        /// Drops the character carried, they make it heavier
+        water: u32,
+    },
+    /// `player` is the character now being controlled
+    Switch {
+        player: usize,
+        position: Vec2,
+    },
+    Offering {
+        /// Title of the NPC
+        npc: String,
+        role: NpcRole,
+        drops: u32,
+        score: u32,
+    },
+    Reset,
+    GameOver {
+        score: u32,
+    },
+}
+
 #[derive(Component)]
 pub struct Game;
 
 #[derive(Component)]
-pub struct Player(usize);
+pub struct Player(pub usize);
+
+impl Player {
+    pub fn name(&self) -> &'static str {
+        if self.0 == 1 {
+            "Nena"
+        } else {
+            "Nata"
+        }
+    }
+}
 
 #[derive(Component, Default)]
-struct PlayerMovement {
+pub struct PlayerMovement {
     x_per_second: f32,
-    y_per_second: f32,
-    airborne: bool,
-    falling: bool,
-    timer: Timer,
+    motion: Motion,
+    jump_held: bool,
+    /// Velocity of the platform the character stands on, see `platform_riding_system`
+    carried: Vec2,
     is_jump_reset: bool,
+    /// Seconds left to jump after walking off a platform
+    coyote_seconds: f32,
+    /// Seconds left for a jump pressed in the air to start on landing
+    buffered_jump_seconds: f32,
+}
+
+impl PlayerMovement {
+    /// Walks at `x_per_second`, for characters not driven by `keyboard_input_system`.
+    pub fn walk(&mut self, x_per_second: f32) {
+        self.x_per_second = x_per_second;
+    }
+
+    /// Holding jump keeps the character rising longer, see `player_gravity_system`
+    pub fn hold_jump(&mut self, held: bool) {
+        self.jump_held = held;
+    }
+
+    pub fn is_rising(&self) -> bool {
+        self.motion.rising
+    }
+
+    /// Starts a jump while carrying `water` drops, using up any late or early jump.
+    pub fn jump(&mut self, model: &MovementModel, water: u32) {
+        self.is_jump_reset = false;
+        self.coyote_seconds = 0.0;
+        self.buffered_jump_seconds = 0.0;
+        model.jump(&mut self.motion, water);
+    }
+}
+
+/// Grace windows that make jumps more forgiving, e.g. on fast clouds.
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct JumpAssist {
+    /// How long after leaving a platform a jump still starts
+    pub coyote_seconds: f32,
+    /// How long before landing a jump press is kept
+    pub buffer_seconds: f32,
+}
+
+impl Default for JumpAssist {
+    fn default() -> Self {
+        JumpAssist {
+            coyote_seconds: 0.1,
+            buffer_seconds: 0.15,
+        }
+    }
 }
 
+/// Marks the characters that read input. Both carry it in co-op.
 #[derive(Component)]
 pub struct ActivePlayer;
 
+/// Whether one player switches between Nata and Nena or two players drive both at once.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, Resource, Serialize, Deserialize)]
+pub enum PlayMode {
+    #[default]
+    Solo,
+    Coop,
+    /// Solo, with the other character following along, see `companion`
+    Companion,
+}
+
+impl PlayMode {
+    /// String ID of the mode's name in the menu
+    pub fn label(&self) -> &'static str {
+        match self {
+            PlayMode::Solo => "players.solo",
+            PlayMode::Coop => "players.coop",
+            PlayMode::Companion => "players.companion",
+        }
+    }
+
+    pub fn next(&self) -> PlayMode {
+        match self {
+            PlayMode::Solo => PlayMode::Companion,
+            PlayMode::Companion => PlayMode::Coop,
+            PlayMode::Coop => PlayMode::Solo,
+        }
+    }
+}
+
+/// Preset picked in the menu next to the game length that makes the sky more or less generous.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, Resource, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    /// String ID of the preset's name
+    pub fn label(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "difficulty.easy",
+            Difficulty::Normal => "difficulty.normal",
+            Difficulty::Hard => "difficulty.hard",
+        }
+    }
+
+    pub fn next(&self) -> Difficulty {
+        match self {
+            Difficulty::Easy => Difficulty::Normal,
+            Difficulty::Normal => Difficulty::Hard,
+            Difficulty::Hard => Difficulty::Easy,
+        }
+    }
+
+    /// How much more likely clouds, drops and gusts are to spawn than on Normal.
+    pub fn spawn_rate(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 1.5,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 0.6,
+        }
+    }
+
+    /// Multiplies the velocity of every cloud.
+    pub fn cloud_speed(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.75,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.3,
+        }
+    }
+
+    /// Seconds before an uncollected drop evaporates.
+    pub fn drop_seconds(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 45.0,
+            Difficulty::Normal => 30.0,
+            Difficulty::Hard => 20.0,
+        }
+    }
+
+    /// Multiplies how much carried water shortens jumps, see `MovementModel::jump_seconds`.
+    pub fn weight_penalty(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.6,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.3,
+        }
+    }
+
+    /// A spawner's `probability` range resized so rolling a 1 is `spawn_rate` times as likely.
+    pub fn spawn_probability(&self, probability: &Range<i32>) -> Range<i32> {
+        let len = (probability.len() as f32 / self.spawn_rate())
+            .round()
+            .max(1.0) as i32;
+        probability.start..probability.start + len
+    }
+}
+
 #[derive(Component, Default)]
 pub struct CloudSpawner {
-    image: Handle<Image>,
-    group: usize,
-    min_velocity: Vec2,
-    max_velocity: Vec2,
-    min_height: f32,
-    max_height: f32,
-    min_time: Timer,
-    max_time: Timer,
-    retry_time: Timer,
-    probability: Range<i32>,
-    collider: Collider,
+    pub image: Handle<Image>,
+    pub group: usize,
+    pub min_velocity: Vec2,
+    pub max_velocity: Vec2,
+    pub min_height: f32,
+    pub max_height: f32,
+    pub min_time: Timer,
+    pub max_time: Timer,
+    pub retry_time: Timer,
+    pub probability: Range<i32>,
+    pub collider: Collider,
 }
 
 #[derive(Component, Default)]
 pub struct WaterCollectableSpawner {
-    image: Handle<Image>,
-    min_height: f32,
-    max_height: f32,
-    min_time: Timer,
-    max_time: Timer,
-    retry_time: Timer,
-    probability: Range<i32>,
+    pub image: Handle<Image>,
+    pub min_height: f32,
+    pub max_height: f32,
+    pub min_time: Timer,
+    pub max_time: Timer,
+    pub retry_time: Timer,
+    pub probability: Range<i32>,
 }
 
 #[derive(Component)]
 pub struct Cloud {
-    group: usize,
-    velocity: Vec2,
+    pub group: usize,
+    /// Clouds blow right to left, see `Cloud::drift`
+    pub velocity: Vec2,
+}
+
+impl Cloud {
+    /// Pixels per second the cloud moves by.
+    pub fn drift(&self) -> Vec2 {
+        Vec2::new(-self.velocity.x, 0.0)
+    }
 }
 
+/// The player whose dialog this box shows.
 #[derive(Component)]
-pub struct DialogBox;
+pub struct DialogBox(Entity);
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, Resource)]
-pub struct DialogSpeaker(Option<Entity>);
+/// The text of a dialog box, revealed by `dialog_typewriter_system` for this player.
+#[derive(Component)]
+pub struct DialogText(Entity);
+
+/// Dialog state of one player: the speaker in range, the node shown and the highlighted choice.
+#[derive(Component, Clone, Debug, Default)]
+pub struct PlayerDialog {
+    speaker: Option<Entity>,
+    /// `None` while the dialog box is closed
+    node: Option<String>,
+    /// The node's text split to fit the dialog box
+    pages: Vec<String>,
+    page: usize,
+    /// Characters of the current page shown so far
+    revealed: usize,
+    reveal: Timer,
+    blip: Option<String>,
+    /// Index into the node's choices and label of every choice offered
+    choices: Vec<(usize, String)>,
+    selection: usize,
+}
+
+impl PlayerDialog {
+    pub fn is_open(&self) -> bool {
+        self.node.is_some()
+    }
+
+    fn page_len(&self) -> usize {
+        self.pages
+            .get(self.page)
+            .map(|p| p.chars().count())
+            .unwrap_or_default()
+    }
+
+    fn page_revealed(&self) -> bool {
+        self.revealed >= self.page_len()
+    }
+
+    /// Choices can be picked once the last page is fully shown.
+    pub fn choosing(&self) -> bool {
+        self.page + 1 >= self.pages.len() && self.page_revealed()
+    }
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, Deref, DerefMut, Resource)]
-pub struct DialogSpeakerOpenDialog(bool);
+    fn open(&mut self, name: &str, pages: Vec<String>, choices: Vec<(usize, String)>) {
+        self.node = Some(name.to_string());
+        self.pages = pages;
+        self.page = 0;
+        self.revealed = 0;
+        self.reveal = Timer::from_seconds(SECONDS_PER_CHARACTER, TimerMode::Repeating);
+        self.choices = choices;
+        self.selection = 0;
+    }
+}
 
-#[derive(Clone, Debug, Default, Resource)]
-pub struct DialogDecisionSelection(usize);
+const SECONDS_PER_CHARACTER: f32 = 0.03;
+/// The blip plays once for this many revealed characters
+const CHARACTERS_PER_BLIP: usize = 3;
 
 #[derive(Component)]
 pub struct Selectable;
@@ -134,7 +413,8 @@ pub struct TextIndicator;
 #[derive(Component)]
 pub struct Dialog {
     pub image: Handle<Image>,
-    pub dialog: Text,
+    pub tree: Handle<DialogTree>,
+    pub style: TextStyle,
     pub title: String,
     pub subtitle: String,
 }
@@ -150,12 +430,64 @@ pub struct WindGust {
 }
 
 #[derive(Component)]
-pub struct WaterCollectable(Timer);
+pub struct WaterCollectable(pub Timer);
+
+/// Marks characters standing on a `WindGust`.
+#[derive(Component)]
+pub struct RidingGust;
+
+/// The `Platform` a character is dropping through after Down + Jump, removed once they are below it.
+#[derive(Component)]
+pub struct DropThrough(pub Entity);
 
 #[derive(Component)]
 pub struct Platform {
     // generally half of player sprite
-    height_adjustment: f32,
+    pub height_adjustment: f32,
+}
+
+/// Makes platforms one-way: solid to a character whose feet are above them and not dropping
+/// through, judged separately for every character and platform.
+///
+/// Rapier asks this through `BevyPhysicsHooks` for every contact pair with a platform, which
+/// carries `ActiveHooks::FILTER_CONTACT_PAIRS`. Character controllers only cast shapes and never
+/// reach the hooks, so `one_way_platform_system` hands them the same judgement through collision
+/// groups.
+#[derive(SystemParam)]
+pub struct OneWayPlatforms<'w, 's> {
+    platforms: Query<'w, 's, (&'static GlobalTransform, &'static Platform)>,
+    players: Query<'w, 's, (&'static GlobalTransform, Option<&'static DropThrough>), With<Player>>,
+}
+
+impl OneWayPlatforms<'_, '_> {
+    /// Whether `collider` blocks `player`. Anything that isn't a platform, or isn't hit by a
+    /// character, always does.
+    pub fn is_solid(&self, player: Entity, collider: Entity) -> bool {
+        let (platform_transform, platform) = match self.platforms.get(collider) {
+            Ok(p) => p,
+            Err(_) => return true,
+        };
+        let (player_transform, drop_through) = match self.players.get(player) {
+            Ok(p) => p,
+            Err(_) => return true,
+        };
+        if drop_through.is_some_and(|d| d.0 == collider) {
+            return false;
+        }
+        let player_bottom = player_transform.translation().y - PLAYER_SPRITE_SIZE_Y;
+        player_bottom >= platform_transform.translation().y - platform.height_adjustment
+    }
+}
+
+impl BevyPhysicsHooks for OneWayPlatforms<'_, '_> {
+    fn filter_contact_pair(&self, context: PairFilterContextView) -> Option<SolverFlags> {
+        let (collider1, collider2) = (context.collider1(), context.collider2());
+        if self.is_solid(collider1, collider2) && self.is_solid(collider2, collider1) {
+            Some(SolverFlags::COMPUTE_IMPULSES)
+        } else {
+            None
+        }
+    }
 }
 
 #[derive(Component)]
@@ -176,45 +508,61 @@ pub struct MovingPlatform {
     pub velocity: Vec2,
 }
 
+/// How the round is played, what it has counted so far and the events that report it.
+#[derive(SystemParam)]
+pub struct RoundRecord<'w> {
+    run_seed: Res<'w, RunSeed>,
+    play_mode: Res<'w, PlayMode>,
+    difficulty: Res<'w, Difficulty>,
+    total_score: ResMut<'w, TotalScore>,
+    water_collection: ResMut<'w, WaterCollection>,
+    stats: ResMut<'w, RoundStats>,
+    events: EventWriter<'w, GameEvent>,
+}
+
+/// The length of the round, the time left in it and the time to the next gust.
+#[derive(SystemParam)]
+pub struct RoundClock<'w> {
+    total_seconds: Res<'w, TotalSeconds>,
+    total_time: ResMut<'w, TotalTime>,
+    gust_timer: ResMut<'w, GustTimer>,
+}
+
 pub fn setup(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
-    mut rng: ResMut<GlobalEntropy<ChaCha8Rng>>,
-    mut total_time: ResMut<TotalTime>,
-    mut total_score: ResMut<TotalScore>,
-    mut water_collection: ResMut<WaterCollection>,
-    total_seconds: Res<TotalSeconds>,
+    mut record: RoundRecord,
+    mut clock: RoundClock,
     mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
     mut bg: ResMut<ClearColor>,
+    strings: Res<Strings>,
 ) {
     bg.0 = Color::BLACK;
-    let text_style = TextStyle {
-        font: asset_server.load("fonts/PressStart2P-vaV7.ttf"),
-        font_size: 18.0,
-        ..default()
-    };
-
-    total_time
+    let total_seconds = clock.total_seconds.0;
+    clock
+        .total_time
         .0
-        .set_duration(Duration::from_secs_f32(total_seconds.0));
-    total_time.0.reset();
-    total_score.0 = 0;
-    *water_collection = WaterCollection::default();
-
-    commands.spawn((
-        Game,
-        RigidBody::Fixed,
-        Collider::polyline(
-            vec![
-                Vec2::new(-599.0, -725.0),
-                Vec2::new(599., -725.),
-                Vec2::new(599., 11200.),
-                Vec2::new(-599., 11200.),
-                Vec2::new(-599.0, -725.0),
-            ],
-            None,
-        ),
+        .set_duration(Duration::from_secs_f32(total_seconds));
+    clock.total_time.0.reset();
+    record.total_score.0 = 0;
+    *record.water_collection = WaterCollection::default();
+    clock.gust_timer.set_duration(Duration::from_secs_f32(
+        GUST_SECONDS / record.difficulty.spawn_rate(),
     ));
+    clock.gust_timer.reset();
+
+    let play_mode = *record.play_mode;
+    record.events.send(GameEvent::Start {
+        seed: record.run_seed.seed,
+        seconds: total_seconds,
+        play_mode,
+        difficulty: *record.difficulty,
+    });
+
+    let (player1_controls, player2_controls) = match play_mode {
+        PlayMode::Solo | PlayMode::Companion => (PlayerControls::Solo, PlayerControls::Solo),
+        PlayMode::Coop => (PlayerControls::First, PlayerControls::Second),
+    };
 
     let texture_handle = asset_server.load("person.png");
     let layout = TextureAtlasLayout::from_grid(
@@ -229,12 +577,10 @@ pub fn setup(
         .spawn((
             Game,
             Player(1),
-            PlayerMovement {
-                timer: Timer::from_seconds(0.4, TimerMode::Once),
-                falling: true,
-                ..default()
-            },
+            PlayerMovement::default(),
             ActivePlayer,
+            player1_controls,
+            PlayerDialog::default(),
             Collider::cuboid(20.0, 25.),
             KinematicCharacterController {
                 filter_groups: Some(CollisionGroups::new(
@@ -274,44 +620,44 @@ pub fn setup(
         Some(UVec2::new(0, 0)),
     );
     let texture_atlas_layout = texture_atlas_layouts.add(layout);
-    commands
-        .spawn((
-            Game,
-            Player(2),
-            PlayerMovement {
-                timer: Timer::from_seconds(0.4, TimerMode::Once),
-                falling: true,
-                ..default()
-            },
-            Collider::cuboid(20.0, 25.0),
-            KinematicCharacterController {
-                filter_groups: Some(CollisionGroups::new(
-                    Group::ALL,
-                    Group::from_iter([Group::GROUP_12]),
-                )),
+    let mut player2 = commands.spawn((
+        Game,
+        Player(2),
+        PlayerMovement::default(),
+        player2_controls,
+        PlayerDialog::default(),
+        Collider::cuboid(20.0, 25.0),
+        KinematicCharacterController {
+            filter_groups: Some(CollisionGroups::new(
+                Group::ALL,
+                Group::from_iter([Group::GROUP_12]),
+            )),
+            ..default()
+        },
+        TextureAtlas {
+            layout: texture_atlas_layout.clone(),
+            index: 0,
+        },
+        SpriteBundle {
+            texture: texture_handle.clone(),
+            transform: Transform {
+                translation: Vec3::new(-100.0, 0.0, 11.0),
                 ..default()
             },
-            TextureAtlas {
-                layout: texture_atlas_layout.clone(),
-                index: 0,
-            },
-            SpriteBundle {
-                texture: texture_handle.clone(),
-                transform: Transform {
-                    translation: Vec3::new(-100.0, 0.0, 11.0),
-                    ..default()
-                },
-                sprite: Sprite {
-                    flip_x: false,
-                    ..default()
-                },
+            sprite: Sprite {
+                flip_x: false,
                 ..default()
             },
-        ))
-        .insert(CollisionGroups::new(
-            Group::from(Group::GROUP_1),
-            Group::from(Group::GROUP_1),
-        ));
+            ..default()
+        },
+    ));
+    player2.insert(CollisionGroups::new(
+        Group::from(Group::GROUP_1),
+        Group::from(Group::GROUP_1),
+    ));
+    if play_mode == PlayMode::Coop {
+        player2.insert(ActivePlayer);
+    }
 
     let texture_handle = asset_server.load("players.png");
     let layout = TextureAtlasLayout::from_grid(
@@ -322,330 +668,66 @@ pub fn setup(
         Some(UVec2::new(0, 0)),
     );
     let texture_atlas_layout = texture_atlas_layouts.add(layout);
-    commands.spawn((
-        Game,
-        PlayerIndicator,
-        TextureAtlas {
-            layout: texture_atlas_layout.clone(),
-            index: 0,
-        },
-        ImageBundle {
-            style: Style {
-                position_type: PositionType::Absolute,
-                top: Val::Percent(1.5),
-                right: Val::Percent(1.5),
-                height: Val::Px(52.0),
-                width: Val::Px(40.0),
-
-                ..default()
-            },
-            image: UiImage {
-                texture: texture_handle,
-                ..default()
+    // In co-op both characters are always shown, side by side
+    let indicators = match play_mode {
+        PlayMode::Solo | PlayMode::Companion => vec![(0, 1.5)],
+        PlayMode::Coop => vec![(0, 5.5), (1, 1.5)],
+    };
+    for (index, right) in indicators {
+        commands.spawn((
+            Game,
+            PlayerIndicator,
+            TextureAtlas {
+                layout: texture_atlas_layout.clone(),
+                index,
             },
-            ..default()
-        },
-    ));
-
-    commands.spawn((
-        Game,
-        CloudSpawner {
-            image: asset_server.load("raincloud.png"),
-            group: 0,
-            min_velocity: Vec2::new(5.0, 0.),
-            max_velocity: Vec2::new(55.0, 0.),
-            min_height: -250.,
-            max_height: -160.,
-            min_time: Timer::from_seconds(2.5, TimerMode::Once),
-            max_time: Timer::from_seconds(10., TimerMode::Once),
-            retry_time: Timer::from_seconds(0.1, TimerMode::Repeating),
-            probability: Range { start: 1, end: 200 },
-            collider: Collider::cuboid(56.5, 25.0),
-            ..default()
-        },
-    ));
-
-    commands.spawn((
-        Game,
-        CloudSpawner {
-            image: asset_server.load("cloud1.png"),
-            group: 1,
-            min_height: -150.,
-            max_height: 0.,
-            min_velocity: Vec2::new(10.0, 0.),
-            max_velocity: Vec2::new(75.0, 0.),
-            min_time: Timer::from_seconds(0.5, TimerMode::Once),
-            max_time: Timer::from_seconds(5., TimerMode::Once),
-            retry_time: Timer::from_seconds(0.1, TimerMode::Repeating),
-            probability: Range { start: 1, end: 60 },
-            collider: Collider::cuboid(20., 10.0),
-            ..default()
-        },
-    ));
-    commands.spawn((
-        Game,
-        WaterCollectableSpawner {
-            image: asset_server.load("droplet.png"),
-            // atlas: TextureAtlas {
-            //     layout: texture_atlas_layouts.add(TextureAtlasLayout::from_grid(
-            //         UVec2::new(20, 20),
-            //         1,
-            //         1,
-            //         Some(UVec2::new(0, 0)),
-            //         Some(UVec2::new(0, 0)),
-            //     )),
-            //     index: rng.gen_range(0..1),
-            // },
-            // grid: GridOptions { rows: 1, cols: 1 },
-            min_height: -350.,
-            max_height: 200.,
-            min_time: Timer::from_seconds(1.0, TimerMode::Once),
-            max_time: Timer::from_seconds(60., TimerMode::Once),
-            retry_time: Timer::from_seconds(0.1, TimerMode::Repeating),
-            probability: Range { start: 1, end: 20 },
-            // collider: Collider::cuboid(10., 10.0),
-        },
-    ));
-
-    commands.spawn((
-        Game,
-        CloudSpawner {
-            image: asset_server.load("raincloud.png"),
-            group: 1,
-            min_velocity: Vec2::new(55.0, 0.),
-            max_velocity: Vec2::new(59.0, 0.),
-            min_height: 300.,
-            max_height: 400.,
-            min_time: Timer::from_seconds(2.5, TimerMode::Once),
-            max_time: Timer::from_seconds(6., TimerMode::Once),
-            retry_time: Timer::from_seconds(0.1, TimerMode::Repeating),
-            probability: Range { start: 1, end: 60 },
-            collider: Collider::cuboid(56.5, 25.0),
-            ..default()
-        },
-    ));
-
-    commands.spawn((
-        Game,
-        CloudSpawner {
-            image: asset_server.load("cloud1.png"),
-            group: 1,
-            min_height: 600.,
-            max_height: 700.,
-            min_velocity: Vec2::new(75.0, 0.),
-            max_velocity: Vec2::new(79.0, 0.),
-            min_time: Timer::from_seconds(0.5, TimerMode::Once),
-            max_time: Timer::from_seconds(2.5, TimerMode::Once),
-            retry_time: Timer::from_seconds(0.1, TimerMode::Repeating),
-            probability: Range { start: 1, end: 20 },
-            collider: Collider::cuboid(20., 10.0),
-            ..default()
-        },
-    ));
-    commands.spawn((
-        Game,
-        CloudSpawner {
-            image: asset_server.load("cloud1.png"),
-            group: 1,
-            min_height: 700.,
-            max_height: 800.,
-            min_velocity: Vec2::new(75.0, 0.),
-            max_velocity: Vec2::new(79.0, 0.),
-            min_time: Timer::from_seconds(0.5, TimerMode::Once),
-            max_time: Timer::from_seconds(3., TimerMode::Once),
-            retry_time: Timer::from_seconds(0.1, TimerMode::Repeating),
-            probability: Range { start: 1, end: 35 },
-            collider: Collider::cuboid(20., 10.0),
-            ..default()
-        },
-    ));
-    commands.spawn((
-        Game,
-        CloudSpawner {
-            image: asset_server.load("cloud1.png"),
-            group: 1,
-            min_height: 800.,
-            max_height: 1000.,
-            min_velocity: Vec2::new(75.0, 0.),
-            max_velocity: Vec2::new(79.0, 0.),
-            min_time: Timer::from_seconds(0.5, TimerMode::Once),
-            max_time: Timer::from_seconds(4., TimerMode::Once),
-            retry_time: Timer::from_seconds(0.1, TimerMode::Repeating),
-            probability: Range { start: 1, end: 50 },
-            collider: Collider::cuboid(20., 10.0),
-            ..default()
-        },
-    ));
+            ImageBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Percent(1.5),
+                    right: Val::Percent(right),
+                    height: Val::Px(52.0),
+                    width: Val::Px(40.0),
 
-    let texture_handle = asset_server.load("cloudplatform.png");
-    commands.spawn((
-        Game,
-        RigidBody::Fixed,
-        Collider::cuboid(196.0, 28.5),
-        Platform {
-            height_adjustment: 28.0,
-        }, // sprite_center as(107/2) - half_y as(2.0)
-        CollisionGroups::new(Group::GROUP_10, Group::ALL),
-        SpriteBundle {
-            texture: texture_handle.clone(),
-            transform: Transform {
-                translation: Vec3::new(-404., 200.0, -1.0),
+                    ..default()
+                },
+                image: UiImage {
+                    texture: texture_handle.clone(),
+                    ..default()
+                },
                 ..default()
             },
-            ..default()
-        },
-    ));
+        ));
+    }
 
-    let texture_handle = asset_server.load("cloudplatform.png");
     commands.spawn((
         Game,
-        RigidBody::Fixed,
-        Collider::cuboid(196.0, 28.5),
-        Platform {
-            height_adjustment: 28.0,
-        }, // sprite_center as(107/2) - half_y as(2.0)
-        CollisionGroups::new(Group::GROUP_10, Group::ALL),
-        SpriteBundle {
-            texture: texture_handle.clone(),
-            transform: Transform {
-                translation: Vec3::new(404., 500.0, -1.0),
-                ..default()
+        TimeDisplay,
+        TextBundle::from_sections([
+            TextSection {
+                value: strings.get("hud.time").to_string(),
+                style: TextStyle {
+                    font: asset_server.load("fonts/PressStart2P-vaV7.ttf"),
+                    font_size: 16.0,
+                    ..default()
+                },
             },
-            sprite: Sprite {
-                flip_y: true,
-                flip_x: true,
-                ..default()
+            TextSection {
+                value: String::from("0"),
+                style: TextStyle {
+                    font: asset_server.load("fonts/PressStart2P-vaV7.ttf"),
+                    font_size: 16.0,
+                    ..default()
+                },
             },
+        ])
+        .with_text_justify(JustifyText::Left)
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Percent(2.0),
+            left: Val::Percent(3.0),
             ..default()
-        },
-    ));
-
-    let texture_handle = asset_server.load("mediumcloudplatform.png");
-    commands.spawn((
-        Game,
-        RigidBody::Fixed,
-        Collider::cuboid(250., 18.5),
-        Platform {
-            height_adjustment: 28.0,
-        }, // sprite_center as(107/2) - half_y as(2.0)
-        CollisionGroups::new(Group::GROUP_10, Group::ALL),
-        SpriteBundle {
-            texture: texture_handle.clone(),
-            transform: Transform {
-                translation: Vec3::new(375., 1050.0, -1.0),
-                ..default()
-            },
-            sprite: Sprite {
-                flip_y: true,
-                flip_x: true,
-                ..default()
-            },
-            ..default()
-        },
-    ));
-
-    let texture_handle = asset_server.load("mediumcloudplatform.png");
-    commands.spawn((
-        Game,
-        RigidBody::Fixed,
-        Collider::cuboid(250., 18.5),
-        Platform {
-            height_adjustment: 28.0,
-        }, // sprite_center as(107/2) - half_y as(2.0)
-        CollisionGroups::new(Group::GROUP_10, Group::ALL),
-        SpriteBundle {
-            texture: texture_handle.clone(),
-            transform: Transform {
-                translation: Vec3::new(-375., 1050.0, -1.0),
-                ..default()
-            },
-            sprite: Sprite {
-                flip_y: false,
-                flip_x: false,
-                ..default()
-            },
-            ..default()
-        },
-    ));
-
-    let texture_handle = asset_server.load("topcloud.png");
-    commands.spawn((
-        Game,
-        RigidBody::Fixed,
-        Collider::cuboid(215.5, 30.),
-        Platform {
-            height_adjustment: 28.0,
-        }, // sprite_center as(107/2) - half_y as(2.0)
-        CollisionGroups::new(Group::GROUP_10, Group::ALL),
-        SpriteBundle {
-            texture: texture_handle.clone(),
-            transform: Transform {
-                translation: Vec3::new(0., 1150.0, -1.0),
-                ..default()
-            },
-            sprite: Sprite {
-                flip_y: false,
-                flip_x: false,
-                ..default()
-            },
-            ..default()
-        },
-    ));
-
-    commands.spawn((
-        Game,
-        SpriteBundle {
-            texture: texture_handle.clone(),
-            transform: Transform {
-                translation: Vec3::new(-200.0, 11200.0, 0.0),
-                ..default()
-            },
-            ..default()
-        },
-    ));
-
-    let texture_handle = asset_server.load("gradient.png");
-
-    commands.spawn((
-        Game,
-        SpriteBundle {
-            texture: texture_handle.clone(),
-            transform: Transform {
-                translation: Vec3::new(0.0, 5850.0 - 750., -10.0),
-                ..default()
-            },
-            ..default()
-        },
-    ));
-
-    commands.spawn((
-        Game,
-        TimeDisplay,
-        TextBundle::from_sections([
-            TextSection {
-                value: String::from("TIME "),
-                style: TextStyle {
-                    font: asset_server.load("fonts/PressStart2P-vaV7.ttf"),
-                    font_size: 16.0,
-                    ..default()
-                },
-            },
-            TextSection {
-                value: String::from("0"),
-                style: TextStyle {
-                    font: asset_server.load("fonts/PressStart2P-vaV7.ttf"),
-                    font_size: 16.0,
-                    ..default()
-                },
-            },
-        ])
-        .with_text_justify(JustifyText::Left)
-        .with_style(Style {
-            position_type: PositionType::Absolute,
-            top: Val::Percent(2.0),
-            left: Val::Percent(3.0),
-            ..default()
-        }),
+        }),
     ));
 
     commands.spawn((
@@ -653,7 +735,7 @@ pub fn setup(
         WaterCollectionScoreboard,
         TextBundle::from_sections([
             TextSection {
-                value: String::from("Nata's Water: "),
+                value: strings.format("hud.water", &[("name", &"Nata")]),
                 style: TextStyle {
                     font: asset_server.load("fonts/PressStart2P-vaV7.ttf"),
                     font_size: 16.0,
@@ -669,7 +751,7 @@ pub fn setup(
                 },
             },
             TextSection {
-                value: String::from("\n\nNena's Water: "),
+                value: format!("\n\n{}", strings.format("hud.water", &[("name", &"Nena")])),
                 style: TextStyle {
                     font: asset_server.load("fonts/PressStart2P-vaV7.ttf"),
                     font_size: 16.0,
@@ -685,7 +767,7 @@ pub fn setup(
                 },
             },
             TextSection {
-                value: String::from("\n\nTotal: "),
+                value: format!("\n\n{}", strings.get("hud.total")),
                 style: TextStyle {
                     font: asset_server.load("fonts/PressStart2P-vaV7.ttf"),
                     font_size: 16.0,
@@ -709,81 +791,6 @@ pub fn setup(
             ..default()
         }),
     ));
-
-    let texture_handle = asset_server.load("tlaloc.png");
-    commands.spawn((
-        Game,
-        Dialog {
-            image: texture_handle.clone(),
-            dialog: Text {
-                sections: vec![
-                    TextSection {
-                        value: String::from("Offer me water droplets to collect points."),
-                        style: text_style.clone(),
-                    },
-                    TextSection {
-                        value: String::from("Your offering pleases me. Will you surrender your tribute now to receive points?\n\n▶ No   Yes"),
-                        style: text_style.clone(),
-                    },
-                    TextSection {
-                        value: String::from("Your offering pleases me. Will you surrender your tribute now to receive points?\n\n  No ▶ Yes"),
-                        style: text_style.clone(),
-                    }
-                ],
-                ..default()
-            },
-            title: String::from("Tlaloc"),
-            subtitle: String::from("The god of rain")
-        },
-        SpriteBundle {
-            texture: texture_handle.clone(),
-            transform: Transform {
-                translation: Vec3::new(0., 1250.0, -2.0),
-                ..default()
-            },
-            sprite: Sprite {
-                flip_y: false,
-                flip_x: false,
-                ..default()
-            },
-            ..default()
-        },
-    ));
-
-    let texture_handle = asset_server.load("chalchiuhtlicue.png");
-    commands.spawn((
-        Game,
-        Dialog {
-            image: asset_server.load("chalchiuhtlicue-bust2.png"),
-            dialog: Text {
-                sections: vec![
-                    TextSection {
-                        value: String::from("Collect the water droplets and offer them to Tlaloc (the rain god) at the top of the sky!\n\nRemember, the more water you collect, the heavier you get."),
-                        style: text_style.clone(),
-                    },
-                    TextSection {
-                        value: String::from("You've collected water. Do you want to give it to me to become as light as a wisp?\n\n▶ No   Yes"),
-                        style: text_style.clone(),
-                    },
-                    TextSection {
-                        value: String::from("You've collected water. Do you want to give it to me to become as light as a wisp?\n\n  No ▶ Yes"),
-                        style: text_style.clone(),
-                    }
-                ],
-                ..default()
-            },
-            title: String::from("Chalchiuhtlicue"),
-            subtitle: String::from("The river goddess")
-        },
-        SpriteBundle {
-            texture: texture_handle.clone(),
-            transform: Transform {
-                translation: Vec3::new(-450.0, -690.0, -9.0),
-                ..default()
-            },
-            ..default()
-        },
-    ));
 }
 
 fn player_indicator_system(
@@ -811,11 +818,13 @@ fn player_indicator_system(
 fn time_count_system(
     time: Res<Time>,
     mut app_state: ResMut<NextState<AppState>>,
-    dialog_speaker_open_dialog: Res<DialogSpeakerOpenDialog>,
     mut total_time: ResMut<TotalTime>,
+    total_score: Res<TotalScore>,
+    mut game_events: EventWriter<GameEvent>,
+    player_dialog_query: Query<&PlayerDialog>,
     mut time_display_query: Query<&mut Text, With<TimeDisplay>>,
 ) {
-    if dialog_speaker_open_dialog.0 {
+    if player_dialog_query.iter().any(|d| d.is_open()) {
         return;
     }
     total_time.0.tick(time.delta());
@@ -824,53 +833,59 @@ fn time_count_system(
         Err(_) => {}
     }
     if total_time.just_finished() {
+        game_events.send(GameEvent::GameOver {
+            score: total_score.0,
+        });
         app_state.set(AppState::GameOver);
     }
 }
 
-pub fn platform_sensor_system(
+/// Ends a `DropThrough` once the character is below the platform, or the platform is gone.
+fn drop_through_system(
     mut commands: Commands,
-    player: Query<(Entity, &Player, &Transform, &KinematicCharacterController), With<Player>>,
-    rapier_context: Res<RapierContext>,
-    mut platform_query: Query<
-        (Entity, &GlobalTransform, &Platform, &mut CollisionGroups),
-        (Without<Player>, With<Platform>),
-    >,
+    player_query: Query<(Entity, &Transform, &DropThrough), With<Player>>,
+    platform_query: Query<(&GlobalTransform, &Platform)>,
 ) {
-    for (player_entity, _player, player_transform, kinematic_controller) in player.iter() {
+    for (player_entity, player_transform, drop_through) in player_query.iter() {
         let player_bottom = player_transform.translation.y - (PLAYER_SPRITE_SIZE_Y);
-
-        let filter_groups = kinematic_controller.filter_groups.unwrap().filters;
-
-        for (platform_entity, platform_transform, platform, mut platform_collision_groups) in
-            platform_query.iter_mut()
-        {
-            let platform_height = platform_transform.translation().y - platform.height_adjustment;
-
-            if player_bottom >= platform_height {
-                platform_collision_groups.memberships.insert(filter_groups);
-            } else {
-                platform_collision_groups.memberships.remove(filter_groups);
+        let clear = match platform_query.get(drop_through.0) {
+            Ok((platform_transform, platform)) => {
+                player_bottom < platform_transform.translation().y - platform.height_adjustment
             }
+            Err(_) => true,
+        };
+        if clear {
+            commands.entity(player_entity).remove::<DropThrough>();
         }
     }
 }
 
+/// The drop spawners, the drops they spawned, the characters that pick them up and the
+/// scoreboard that counts them.
+#[derive(SystemParam)]
+struct Drops<'w, 's> {
+    rapier_context: Res<'w, RapierContext>,
+    spawners: Query<'w, 's, &'static mut WaterCollectableSpawner>,
+    collectables: Query<
+        'w,
+        's,
+        (Entity, &'static Transform, &'static mut WaterCollectable),
+        With<WaterCollectable>,
+    >,
+    players: Query<'w, 's, (Entity, &'static Player, Has<RidingGust>)>,
+    scoreboard: Query<'w, 's, &'static mut Text, With<WaterCollectionScoreboard>>,
+}
+
 fn collectable_system(
     mut commands: Commands,
     time: Res<Time>,
     asset_server: Res<AssetServer>,
     mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
-    rapier_context: Res<RapierContext>,
     mut rng: ResMut<GlobalEntropy<ChaCha8Rng>>,
-    mut water_collection: ResMut<WaterCollection>,
-    total_score: Res<TotalScore>,
-    mut water_collectable_spawner_query: Query<&mut WaterCollectableSpawner>,
-    mut water_collectable_query: Query<(Entity, &mut WaterCollectable), With<WaterCollectable>>,
-    player_query: Query<(Entity, &Player)>,
-    mut scoreboard_query: Query<&mut Text, With<WaterCollectionScoreboard>>,
+    mut record: RoundRecord,
+    mut drops: Drops,
 ) {
-    for mut spawner in water_collectable_spawner_query.iter_mut() {
+    for mut spawner in drops.spawners.iter_mut() {
         spawner.min_time.tick(time.delta());
         spawner.max_time.tick(time.delta());
         spawner.retry_time.tick(time.delta());
@@ -894,10 +909,22 @@ fn collectable_system(
                 spawner.min_time.reset();
                 spawner.max_time.reset();
 
+                let index = rng.gen_range(0..1);
+                let translation = Vec3::new(
+                    rng.gen_range(-580.0..=580.0),
+                    rng.gen_range(spawner.min_height..spawner.max_height),
+                    0.0,
+                );
+                record.events.send(GameEvent::DropSpawned {
+                    position: translation.truncate(),
+                });
                 commands
                     .spawn((
                         Game,
-                        WaterCollectable(Timer::from_seconds(30.0, TimerMode::Once)),
+                        WaterCollectable(Timer::from_seconds(
+                            record.difficulty.drop_seconds(),
+                            TimerMode::Once,
+                        )),
                         Sensor,
                         Collider::cuboid(10., 10.0),
                         // CollisionGroups::new(Group::GROUP_12 | Group::GROUP_13, Group::ALL),
@@ -909,16 +936,12 @@ fn collectable_system(
                                 Some(UVec2::new(0, 0)),
                                 Some(UVec2::new(0, 0)),
                             )),
-                            index: rng.gen_range(0..1),
+                            index,
                         },
                         SpriteBundle {
                             texture: spawner.image.clone(),
                             transform: Transform {
-                                translation: Vec3::new(
-                                    rng.gen_range(-580.0..=580.0),
-                                    rng.gen_range(spawner.min_height..spawner.max_height),
-                                    0.0,
-                                ),
+                                translation,
                                 ..default()
                             },
                             ..default()
@@ -928,22 +951,31 @@ fn collectable_system(
             }
         }
     }
-    for (entity, mut water_collectable) in water_collectable_query.iter_mut() {
+    for (entity, transform, mut water_collectable) in drops.collectables.iter_mut() {
+        let position = transform.translation.truncate();
         water_collectable.0.tick(time.delta());
         if water_collectable.0.just_finished() {
+            record.events.send(GameEvent::DropExpired { position });
             commands.entity(entity).despawn_recursive();
             continue;
         }
-        for (player_entity, player) in player_query.iter() {
-            if rapier_context
+        for (player_entity, player, riding_gust) in drops.players.iter() {
+            if drops
+                .rapier_context
                 .intersection_pair(entity, player_entity)
                 .is_some()
             {
                 if player.0 == 1 {
-                    water_collection.total_player1 += 1;
+                    record.water_collection.total_player1 += 1;
                 } else {
-                    water_collection.total_player2 += 1;
+                    record.water_collection.total_player2 += 1;
                 }
+                record.stats.player(player).collected += 1;
+                record.events.send(GameEvent::DropCollected {
+                    player: player.0,
+                    position,
+                    riding_gust,
+                });
                 commands.spawn(AudioBundle {
                     source: asset_server.load("collect.mp3"),
                     settings: PlaybackSettings {
@@ -956,11 +988,11 @@ fn collectable_system(
         }
     }
 
-    let mut score = scoreboard_query.single_mut();
+    let mut score = drops.scoreboard.single_mut();
 
-    score.sections[1].value = water_collection.total_player2.to_string();
-    score.sections[3].value = water_collection.total_player1.to_string();
-    score.sections[5].value = total_score.0.to_string();
+    score.sections[1].value = record.water_collection.total_player2.to_string();
+    score.sections[3].value = record.water_collection.total_player1.to_string();
+    score.sections[5].value = record.total_score.0.to_string();
 }
 
 fn cloud_movement(
@@ -1003,12 +1035,14 @@ fn cloud_movement(
                             rng.gen_range(spawner.min_velocity.y..=spawner.max_velocity.y),
                         ),
                     },
-                    RigidBody::KinematicPositionBased,
+                    // Moved by hand, riders are carried by `platform_riding_system` alone
+                    RigidBody::Fixed,
                     spawner.collider.clone(),
                     Platform {
                         height_adjustment: 25.0,
                     }, // sprite_center as(107/2) - half_y as(2.0)
                     CollisionGroups::new(Group::GROUP_10, Group::ALL),
+                    ActiveHooks::FILTER_CONTACT_PAIRS,
                     SpriteBundle {
                         texture: spawner.image.clone(),
                         transform: Transform {
@@ -1026,12 +1060,12 @@ fn cloud_movement(
         }
     }
 
-    for (entity, mut transform, wind) in clouds.iter_mut() {
+    for (entity, mut transform, cloud) in clouds.iter_mut() {
         if transform.translation.x < -680.0 {
             commands.entity(entity).despawn_recursive();
             continue;
         }
-        transform.translation.x -= wind.velocity.x * time.delta_seconds();
+        transform.translation += (cloud.drift() * time.delta_seconds()).extend(0.0);
     }
 }
 
@@ -1098,12 +1132,13 @@ fn gust_system(
                 .with_children(|child| {
                     child.spawn((
                         Game,
-                        RigidBody::KinematicPositionBased,
+                        RigidBody::Fixed,
                         Collider::cuboid(50.0, 2.0),
                         Platform {
                             height_adjustment: 51.5,
                         }, // sprite_center as(107/2) - half_y as(2.0)
                         CollisionGroups::new(Group::GROUP_10, Group::ALL),
+                        ActiveHooks::FILTER_CONTACT_PAIRS,
                         TransformBundle::from_transform(Transform {
                             translation: Vec3::new(0., 40.0, -9.0),
                             ..default()
@@ -1142,61 +1177,118 @@ fn gust_system(
     }
 }
 
+fn gust_riding_system(
+    mut commands: Commands,
+    player_query: Query<(Entity, &Player, Option<&KinematicCharacterControllerOutput>)>,
+    riding_query: Query<(), With<RidingGust>>,
+    platform_query: Query<&Parent, With<Platform>>,
+    gust_query: Query<(), With<WindGust>>,
+) {
+    for (entity, _, output) in player_query.iter() {
+        let riding = riding_query.contains(entity);
+        let on_gust = output.is_some_and(|o| {
+            o.grounded
+                && o.collisions.iter().any(|c| {
+                    platform_query
+                        .get(c.entity)
+                        .is_ok_and(|p| gust_query.contains(p.get()))
+                })
+        });
+        if on_gust && !riding {
+            commands.entity(entity).insert(RidingGust);
+        } else if !on_gust && riding {
+            commands.entity(entity).remove::<RidingGust>();
+        }
+    }
+}
+
 fn player_gravity_system(
     time: Res<Time>,
-    mut water_collection: Res<WaterCollection>,
-    mut player_query: Query<(&mut PlayerMovement, &Player)>,
+    movement_model: Res<MovementModel>,
+    mut player_query: Query<&mut PlayerMovement>,
 ) {
-    for (mut player_movement, player) in player_query.iter_mut() {
-        let water_collected = if player.0 == 1 {
-            water_collection.total_player1
-        } else {
-            water_collection.total_player2
-        };
-        let mut gravity_multiplier = if water_collected > 15 {
-            0.4 - 0.015 * (water_collected - 14) as f32
-        } else if water_collected > 8 {
-            0.8 - 0.05 * (water_collected - 7) as f32
-        } else {
-            1.4 - 0.1 * water_collected as f32
-        };
-        if gravity_multiplier < 0.3 {
-            gravity_multiplier = 0.2
-        }
-        player_movement
-            .timer
-            .set_duration(Duration::from_secs_f32(gravity_multiplier));
-        if player_movement.falling {
-            if player_movement.airborne && !player_movement.timer.finished() {
-                player_movement.timer.tick(2 * time.delta());
-                let current_jump_time = player_movement.timer.elapsed().as_secs_f32();
-                let total_jump_time = player_movement.timer.duration().as_secs_f32();
-                let jump_percent = current_jump_time / total_jump_time;
-                player_movement.y_per_second = 600. * (1. - jump_percent);
+    for mut player_movement in player_query.iter_mut() {
+        let jump_held = player_movement.jump_held;
+        movement_model.step(&mut player_movement.motion, jump_held, time.delta_seconds());
+    }
+}
+
+fn translate_player_system(
+    time: Res<Time>,
+    mut player_query: Query<(&mut KinematicCharacterController, &PlayerMovement)>,
+) {
+    for (mut controller, trajectory_velocity) in player_query.iter_mut() {
+        controller.translation = Some(
+            (Vec2::new(
+                trajectory_velocity.x_per_second,
+                trajectory_velocity.motion.velocity,
+            ) + trajectory_velocity.carried)
+                * time.delta_seconds(),
+        )
+    }
+}
+
+/// Makes each platform a member of the filter group of every character `OneWayPlatforms` finds
+/// it solid for, which is all a character controller checks.
+fn one_way_platform_system(
+    one_way_platforms: OneWayPlatforms,
+    player_query: Query<(Entity, &KinematicCharacterController), With<Player>>,
+    mut platform_query: Query<(Entity, &mut CollisionGroups), With<Platform>>,
+) {
+    for (platform, mut groups) in platform_query.iter_mut() {
+        let mut memberships = groups.memberships;
+        for (player, controller) in player_query.iter() {
+            let filter = match controller.filter_groups {
+                Some(groups) => groups.filters,
+                None => continue,
+            };
+            if one_way_platforms.is_solid(player, platform) {
+                memberships.insert(filter);
             } else {
-                let d = (MAXIMUM_DOWNWARD_VELOCITY
-                    - (player_movement.y_per_second + MAXIMUM_DOWNWARD_VELOCITY))
-                    / MAXIMUM_DOWNWARD_VELOCITY;
-                if player_movement.y_per_second > 0. {
-                    player_movement.y_per_second = 0.0;
-                } else if player_movement.y_per_second > -MAXIMUM_DOWNWARD_VELOCITY {
-                    player_movement.y_per_second +=
-                        CHARACTER_DOWNWARD_VELOCITY_PER_FRAME * time.delta_seconds() * d.cos();
-                }
+                memberships.remove(filter);
             }
         }
+        if groups.memberships != memberships {
+            groups.memberships = memberships;
+        }
     }
 }
 
-fn translate_player_system(
-    time: Res<Time>,
-    mut player_query: Query<(&mut KinematicCharacterController, &mut PlayerMovement)>,
+/// Moves grounded characters along with the cloud or gust under their feet.
+///
+/// Clouds and gust platforms are fixed bodies moved through their `Transform`. On a kinematic
+/// body Rapier's character controller would carry the rider as well, twice as far.
+fn platform_riding_system(
+    mut player_query: Query<(
+        &mut PlayerMovement,
+        Option<&KinematicCharacterControllerOutput>,
+    )>,
+    cloud_query: Query<&Cloud>,
+    platform_query: Query<&Parent, With<Platform>>,
+    moving_platform_query: Query<&MovingPlatform>,
 ) {
-    for (mut transform, mut trajectory_velocity) in player_query.iter_mut() {
-        transform.translation = Some(Vec2::new(
-            trajectory_velocity.x_per_second * time.delta_seconds(),
-            trajectory_velocity.y_per_second * time.delta_seconds(),
-        ))
+    let platform_velocity = |entity: Entity| {
+        if let Ok(cloud) = cloud_query.get(entity) {
+            return Some(cloud.drift());
+        }
+        platform_query
+            .get(entity)
+            .and_then(|p| moving_platform_query.get(p.get()))
+            .map(|m| m.velocity)
+            .ok()
+    };
+    for (mut player_movement, output) in player_query.iter_mut() {
+        let carried = output
+            .filter(|o| o.grounded)
+            .and_then(|o| {
+                o.collisions
+                    .iter()
+                    .find_map(|c| platform_velocity(c.entity))
+            })
+            .unwrap_or_default();
+        if player_movement.carried != carried {
+            player_movement.carried = carried;
+        }
     }
 }
 
@@ -1205,6 +1297,7 @@ fn debug_system(
     time: Res<Time>,
     mut dev: ResMut<DevPhase>,
     keyboard_input: Res<ButtonInput<KeyCode>>,
+    actions: Res<Actions>,
     mut active_player_query: Query<
         Entity,
         (With<ActivePlayer>, With<KinematicCharacterController>),
@@ -1266,10 +1359,10 @@ fn debug_system(
         _player_movement,
     ) = active_player_debug.single_mut();
 
-    let left_key_pressed = keyboard_input.pressed(KeyCode::ArrowLeft);
-    let right_key_pressed = keyboard_input.pressed(KeyCode::ArrowRight);
-    let up_key_pressed = keyboard_input.pressed(KeyCode::ArrowUp);
-    let down_key_pressed = keyboard_input.pressed(KeyCode::ArrowDown);
+    let left_key_pressed = actions.any_pressed(Action::NavigateLeft);
+    let right_key_pressed = actions.any_pressed(Action::NavigateRight);
+    let up_key_pressed = actions.any_pressed(Action::NavigateUp);
+    let down_key_pressed = actions.any_pressed(Action::NavigateDown);
 
     if left_key_pressed {
         let x = transform.translation.x - 2.0 * PLAYER_MOVEMENT_SPEED * time.delta_seconds();
@@ -1297,7 +1390,7 @@ fn debug_system(
         }
     }
 
-    let switch_key_just_pressed = keyboard_input.just_pressed(KeyCode::ShiftLeft);
+    let switch_key_just_pressed = actions.any_just_pressed(Action::Switch);
     if switch_key_just_pressed {
         player_query.iter_mut().for_each(|(e, _)| {
             if e == active_player_entity {
@@ -1309,267 +1402,287 @@ fn debug_system(
     }
 }
 
+/// What decides whether a character may jump or drop through a platform: the jump assist and
+/// movement settings, the clock they count down with and what each character stands on.
+#[derive(SystemParam)]
+struct JumpState<'w, 's> {
+    time: Res<'w, Time>,
+    jump_assist: Res<'w, JumpAssist>,
+    movement_model: Res<'w, MovementModel>,
+    kinematic_outputs: Query<'w, 's, (Entity, &'static KinematicCharacterControllerOutput)>,
+    platforms: Query<'w, 's, (), With<Platform>>,
+}
+
+type ActivePlayerQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static Player,
+        &'static Transform,
+        &'static PlayerControls,
+        &'static PlayerDialog,
+        &'static mut Sprite,
+        &'static mut PlayerMovement,
+    ),
+    With<ActivePlayer>,
+>;
+
 fn keyboard_input_system(
     mut commands: Commands,
-    time: Res<Time>,
     asset_server: Res<AssetServer>,
-    dialog_speaker: Res<DialogSpeaker>,
-    dialog_speaker_open_dialog: Res<DialogSpeakerOpenDialog>,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    gamepads: Res<Gamepads>,
-    button_inputs: Res<ButtonInput<GamepadButton>>,
-    axes: Res<Axis<GamepadAxis>>,
-    mut active_player_query: Query<
-        (
-            Entity,
-            &mut KinematicCharacterController,
-            &mut TextureAtlas,
-            &mut Sprite,
-            &mut PlayerMovement,
-        ),
-        With<ActivePlayer>,
-    >,
-    active_player_kinematic_output_query: Query<(Entity, &KinematicCharacterControllerOutput)>,
-    mut player_query: Query<(Entity, &mut Transform), With<Player>>,
+    actions: Res<Actions>,
+    jump_state: JumpState,
+    mut record: RoundRecord,
+    mut active_player_query: ActivePlayerQuery,
+    mut app_state: ResMut<NextState<AppState>>,
 ) {
-    if dialog_speaker_open_dialog.0 {
-        return;
-    }
-    let gamepad = match gamepads.iter().next() {
-        Some(gp) => gp,
-        None => Gamepad::new(0),
-    };
-
-    // *ptime = time::PhysicsTime::default();
-    let pause_key_just_pressed = button_inputs
-        .just_pressed(GamepadButton::new(gamepad, GamepadButtonType::Start))
-        || keyboard_input.just_pressed(KeyCode::KeyP);
-
-    let left_stick_x = match axes.get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX)) {
-        Some(x) => x,
-        None => 0.0,
-    };
-
-    let left_stick_y = match axes.get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickY)) {
-        Some(y) => y,
-        None => 0.0,
+    let model = MovementModel {
+        weight_penalty: record.difficulty.weight_penalty(),
+        ..*jump_state.movement_model
     };
-
-    // TODO figure out why d-pad is not working
-
-    // let left_stick_y = match axes.get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickY)) {
-    //     Some(y) => y,
-    //     None => 0.0,
-    // };
-
-    let jump_key_pressed = button_inputs
-        .pressed(GamepadButton::new(gamepad, GamepadButtonType::South))
-        || keyboard_input.pressed(KeyCode::Space);
-    let jump_key_just_pressed = button_inputs
-        .just_pressed(GamepadButton::new(gamepad, GamepadButtonType::South))
-        || keyboard_input.just_pressed(KeyCode::Space);
-    // let run_key_just_pressed = button_inputs
-    //     .just_pressed(GamepadButton::new(gamepad, GamepadButtonType::West))
-    //     || keyboard_input.just_pressed(KeyCode::ShiftLeft);
-    let switch_key_just_pressed = button_inputs
-        .just_pressed(GamepadButton::new(gamepad, GamepadButtonType::West))
-        || keyboard_input.just_pressed(KeyCode::ShiftLeft);
-    // let left_key_just_pressed =
-    //     left_stick_x < 0.0 || keyboard_input.just_pressed(KeyCode::ArrowLeft);
-    let left_key_pressed = left_stick_x < -0.10 || keyboard_input.pressed(KeyCode::ArrowLeft);
-    // let right_key_just_pressed =
-    //     left_stick_x > 0.0 || keyboard_input.just_pressed(KeyCode::ArrowRight);
-    let right_key_pressed = left_stick_x > 0.10 || keyboard_input.pressed(KeyCode::ArrowRight);
-
-    let up_key_pressed = left_stick_y > 0.10 || keyboard_input.pressed(KeyCode::ArrowUp);
-    // let right_key_just_pressed =
-    //     left_stick_x > 0.0 || keyboard_input.just_pressed(KeyCode::ArrowRight);
-    let down_key_pressed = left_stick_y < -0.10 || keyboard_input.pressed(KeyCode::ArrowDown);
-
-    let up_key_pressed = keyboard_input.pressed(KeyCode::ArrowUp) || left_stick_y > 0.10;
-    let down_key_pressed = keyboard_input.pressed(KeyCode::ArrowDown) || left_stick_y < -0.10;
-
-    let (
-        mut active_player_entity,
-        mut transform,
-        mut texture_atlas,
+    let delta_seconds = jump_state.time.delta_seconds();
+    for (
+        active_player_entity,
+        player,
+        transform,
+        controls,
+        player_dialog,
         mut sprite_image,
         mut player_movement,
-    ) = match active_player_query.get_single_mut() {
-        Ok(e) => e,
-        Err(_) => return,
-    };
-
-    let mut total_x = 0.;
-    let mut total_y = 0.;
-
-    let mut grounded = match active_player_kinematic_output_query
-        .iter()
-        .find(|e| e.0 == active_player_entity)
+    ) in active_player_query.iter_mut()
     {
-        Some((_, k)) => k.grounded,
-        None => false,
-    };
+        if player_dialog.is_open() {
+            continue;
+        }
+        let jump_key_pressed = controls.pressed(&actions, Action::Jump);
+        let jump_key_just_pressed = controls.just_pressed(&actions, Action::Jump);
+        let down_key_pressed = controls.pressed(&actions, Action::NavigateDown);
+        let left_key_pressed = controls.pressed(&actions, Action::MoveLeft);
+        let right_key_pressed = controls.pressed(&actions, Action::MoveRight);
+
+        // *ptime = time::PhysicsTime::default();
+        if controls.just_pressed(&actions, Action::Pause) {
+            app_state.set(AppState::Pause);
+            return;
+        }
 
-    if jump_key_just_pressed {
-        // Checks for dialog and disable jumping when in range of speaker
-        match dialog_speaker.0 {
-            Some(_) => {}
-            None => {
-                if grounded && player_movement.is_jump_reset {
-                    commands.spawn(AudioBundle {
-                        source: asset_server.load("jump.wav"),
-                        settings: PlaybackSettings {
-                            mode: PlaybackMode::Despawn,
-                            ..default()
-                        },
-                    });
+        let mut total_x = 0.;
 
-                    // player can now jump
-                    player_movement.timer.reset();
-                    player_movement.airborne = true;
-                    player_movement.falling = false;
-                    player_movement.is_jump_reset = false;
-                    total_y = 600.0;
-                }
-            }
+        let kinematic_output = jump_state
+            .kinematic_outputs
+            .iter()
+            .find(|e| e.0 == active_player_entity)
+            .map(|(_, k)| k);
+        let grounded = match kinematic_output {
+            Some(k) => k.grounded,
+            None => false,
+        };
+        // The ground itself is not a `Platform`, so there is nothing to drop through there
+        let standing_on = kinematic_output.filter(|k| k.grounded).and_then(|k| {
+            k.collisions
+                .iter()
+                .map(|c| c.entity)
+                .find(|e| jump_state.platforms.contains(*e))
+        });
+
+        if grounded {
+            player_movement.coyote_seconds = jump_state.jump_assist.coyote_seconds;
+        } else {
+            player_movement.coyote_seconds -= delta_seconds;
         }
-    } else if jump_key_pressed {
-        if player_movement.airborne && player_movement.timer.finished() {
-            player_movement.falling = true;
+        player_movement.buffered_jump_seconds -= delta_seconds;
+        // A jump pressed next to a speaker belongs to the dialog
+        if player_dialog.speaker.is_some() {
+            player_movement.buffered_jump_seconds = 0.0;
         }
+        let can_jump = grounded || player_movement.coyote_seconds > 0.0;
+        let buffered_jump = grounded && player_movement.buffered_jump_seconds > 0.0;
 
-        if player_movement.airborne && !player_movement.timer.finished() {
-            player_movement.timer.tick(time.delta());
+        if let Some(platform) = standing_on.filter(|_| {
+            jump_key_just_pressed && down_key_pressed && player_dialog.speaker.is_none()
+        }) {
+            commands
+                .entity(active_player_entity)
+                .insert(DropThrough(platform));
+            // No late jump on the way down either
+            player_movement.is_jump_reset = false;
+            player_movement.coyote_seconds = 0.0;
+        } else if jump_key_just_pressed || buffered_jump {
+            // Checks for dialog and disable jumping when in range of speaker
+            match player_dialog.speaker {
+                Some(_) => {}
+                None => {
+                    // A buffered press was a fresh one, so it doesn't wait for `is_jump_reset`
+                    if can_jump && (player_movement.is_jump_reset || buffered_jump) {
+                        commands.spawn(AudioBundle {
+                            source: asset_server.load("jump.wav"),
+                            settings: PlaybackSettings {
+                                mode: PlaybackMode::Despawn,
+                                ..default()
+                            },
+                        });
 
-            let current_jump_time = player_movement.timer.elapsed().as_secs_f32();
-            let total_jump_time = player_movement.timer.duration().as_secs_f32();
-            let jump_percent = current_jump_time / total_jump_time;
-            total_y = 600. * (1. - jump_percent);
+                        // player can now jump
+                        let water = if player.0 == 1 {
+                            record.water_collection.total_player1
+                        } else {
+                            record.water_collection.total_player2
+                        };
+                        player_movement.jump(&model, water);
+                        record.stats.player(player).jumps += 1;
+                        record.events.send(GameEvent::Jump {
+                            player: player.0,
+                            position: transform.translation.truncate(),
+                            water,
+                        });
+                    } else if jump_key_just_pressed && !grounded {
+                        player_movement.buffered_jump_seconds =
+                            jump_state.jump_assist.buffer_seconds;
+                    }
+                }
+            }
         }
-    }
+        // Holding jump keeps the character rising longer, see `player_gravity_system`
+        player_movement.jump_held = jump_key_pressed;
 
-    if !jump_key_pressed {
-        if player_movement.airborne && !player_movement.timer.finished() {
-            player_movement.timer.tick(2 * time.delta());
-            let current_jump_time = player_movement.timer.elapsed().as_secs_f32();
-            let total_jump_time = player_movement.timer.duration().as_secs_f32();
-            let jump_percent = current_jump_time / total_jump_time;
-            total_y = 600. * (1. - jump_percent);
-        } else {
-            player_movement.falling = true;
+        if !jump_key_pressed && grounded {
+            player_movement.is_jump_reset = true;
         }
 
-        if grounded {
-            player_movement.is_jump_reset = true;
+        if left_key_pressed {
+            total_x = -PLAYER_MOVEMENT_SPEED; //* time.delta_seconds();
+            sprite_image.flip_x = false;
+        } else if right_key_pressed {
+            total_x = PLAYER_MOVEMENT_SPEED; //* time.delta_seconds();
+            sprite_image.flip_x = true;
         }
+
+        player_movement.x_per_second = total_x;
     }
+}
 
-    if left_key_pressed {
-        total_x = -PLAYER_MOVEMENT_SPEED; //* time.delta_seconds();
-        sprite_image.flip_x = false;
-    } else if right_key_pressed {
-        total_x = PLAYER_MOVEMENT_SPEED; //* time.delta_seconds();
-        sprite_image.flip_x = true;
-    }
-
-    if switch_key_just_pressed {
-        player_query.iter_mut().for_each(|(e, mut t)| {
+/// Hands control to the other character when playing alone. The one left behind stops where
+/// it is.
+fn switch_player_system(
+    mut commands: Commands,
+    actions: Res<Actions>,
+    mut game_events: EventWriter<GameEvent>,
+    mut active_player_query: Query<
+        (Entity, &PlayerControls, &PlayerDialog, &mut PlayerMovement),
+        With<ActivePlayer>,
+    >,
+    mut player_query: Query<(Entity, &mut Transform, &Player)>,
+) {
+    for (active_player_entity, controls, player_dialog, mut player_movement) in
+        active_player_query.iter_mut()
+    {
+        if player_dialog.is_open()
+            || *controls != PlayerControls::Solo
+            || !controls.just_pressed(&actions, Action::Switch)
+        {
+            continue;
+        }
+        player_query.iter_mut().for_each(|(e, mut t, player)| {
             if e == active_player_entity {
                 commands.entity(e).remove::<ActivePlayer>();
                 t.translation.z = 10.0;
             } else {
                 commands.entity(e).insert(ActivePlayer);
                 t.translation.z = 11.0;
+                game_events.send(GameEvent::Switch {
+                    player: player.0,
+                    position: t.translation.truncate(),
+                });
             }
         });
-        total_x = 0.;
-        player_movement.falling = true;
-    }
-
-    if !player_movement.falling {
-        player_movement.y_per_second = total_y;
+        player_movement.x_per_second = 0.;
+        player_movement.jump_held = false;
     }
-    player_movement.x_per_second = total_x;
 }
 
 fn dialog_selection_system(
-    mut time: ResMut<Time>,
     mut commands: Commands,
     asset_server: Res<AssetServer>,
-
-    mut dialog_speaker: ResMut<DialogSpeaker>,
-    mut dialog_speaker_open_dialog: ResMut<DialogSpeakerOpenDialog>,
     dialog_query: Query<(Entity, &Dialog, &Transform)>,
-    mut text_indicator_query: Query<Entity, With<TextIndicator>>,
     selected_text_query: Query<Entity, With<TextIndicatorParentSelector>>,
+    indicator_query: Query<(Entity, &Parent), With<TextIndicator>>,
     mut active_player_query: Query<
-        ((&Transform, &mut PlayerMovement)),
+        (&Transform, &mut PlayerMovement, &mut PlayerDialog),
         (With<Player>, With<ActivePlayer>),
     >,
 ) {
-    let (player_position, mut player_movement) = active_player_query.single_mut();
-    let mut dialogs = dialog_query
-        .iter()
-        .filter(|(_, _, t)| {
-            (t.translation.x - player_position.translation.x).abs()
-                + (t.translation.y - player_position.translation.y).abs()
-                < 100.
-        })
-        .map(|(e, d, t)| {
-            (
-                e,
-                d,
-                t,
+    let mut speakers = Vec::new();
+    for (player_position, mut player_movement, mut player_dialog) in active_player_query.iter_mut()
+    {
+        let mut dialogs = dialog_query
+            .iter()
+            .filter(|(_, _, t)| {
                 (t.translation.x - player_position.translation.x).abs()
-                    + (t.translation.y - player_position.translation.y).abs(),
-            )
-        })
-        .collect::<Vec<_>>();
+                    + (t.translation.y - player_position.translation.y).abs()
+                    < 100.
+            })
+            .map(|(e, d, t)| {
+                (
+                    e,
+                    d,
+                    t,
+                    (t.translation.x - player_position.translation.x).abs()
+                        + (t.translation.y - player_position.translation.y).abs(),
+                )
+            })
+            .collect::<Vec<_>>();
 
-    dialogs.sort_by(|(_, _, _, a), (_, _, _, b)| a.total_cmp(b));
+        dialogs.sort_by(|(_, _, _, a), (_, _, _, b)| a.total_cmp(b));
 
-    let (mut entity, dialog, _, _) = match dialogs.first() {
-        Some(d) => {
-            player_movement.is_jump_reset = false;
-            d
-        }
-        None => {
-            for entity in text_indicator_query.iter_mut() {
-                commands.entity(entity).despawn();
+        player_dialog.speaker = match dialogs.first() {
+            Some((entity, _, _, _)) => {
+                player_movement.is_jump_reset = false;
+                if !speakers.contains(entity) {
+                    speakers.push(*entity);
+                }
+                Some(*entity)
             }
+            None => None,
+        };
+    }
 
-            *dialog_speaker = DialogSpeaker(None);
-            return;
-        }
-    };
-
-    commands.entity(entity).insert(TextIndicatorParentSelector);
+    // Speakers nobody is next to anymore lose their indicator, and only that, shrines keep
+    // their offering zone
     for e in selected_text_query.iter() {
-        if e != entity {
+        if !speakers.contains(&e) {
             commands.entity(e).remove::<TextIndicatorParentSelector>();
-            commands.entity(e).despawn_descendants();
-        } else {
-            if text_indicator_query.is_empty() {
-                commands.entity(entity).with_children(|child| {
-                    let texture_handle = asset_server.load("textindicator.png");
-                    child.spawn((
-                        Game,
-                        TextIndicator,
-                        SpriteBundle {
-                            texture: texture_handle.clone(),
-                            transform: Transform {
-                                translation: Vec3::new(0.0, 50.0, 0.0),
-                                ..default()
-                            },
+        }
+    }
+    for (indicator, parent) in indicator_query.iter() {
+        if !speakers.contains(&parent.get()) {
+            commands.entity(indicator).despawn_recursive();
+        }
+    }
+
+    for entity in speakers {
+        if selected_text_query.contains(entity) {
+            continue;
+        }
+        commands
+            .entity(entity)
+            .insert(TextIndicatorParentSelector)
+            .with_children(|child| {
+                let texture_handle = asset_server.load("textindicator.png");
+                child.spawn((
+                    Game,
+                    TextIndicator,
+                    SpriteBundle {
+                        texture: texture_handle.clone(),
+                        transform: Transform {
+                            translation: Vec3::new(0.0, 50.0, 0.0),
                             ..default()
                         },
-                    ));
-                });
-            }
-        }
+                        ..default()
+                    },
+                ));
+            });
     }
-    *dialog_speaker = DialogSpeaker(Some(entity));
 }
 
 fn resetting(
@@ -1649,343 +1762,95 @@ fn resetting(
     }
 }
 
-fn active_dialog_system(
-    mut time: ResMut<Time>,
-    mut commands: Commands,
-    mut yes_or_no: ResMut<DialogDecisionSelection>,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    gamepads: Res<Gamepads>,
-    asset_server: Res<AssetServer>,
-    axes: Res<Axis<GamepadAxis>>,
-    mut water_collection: ResMut<WaterCollection>,
-    mut total_score: ResMut<TotalScore>,
-    mut game_phase: ResMut<GamePhase>,
-    button_inputs: Res<ButtonInput<GamepadButton>>,
-    dialog_speaker: ResMut<DialogSpeaker>,
-    mut dialog_speaker_open_dialog: ResMut<DialogSpeakerOpenDialog>,
-    open_dialog: Query<Entity, With<DialogBox>>,
-    mut dialog_query: Query<(Entity, &Dialog, &Transform)>,
-    mut player_query: Query<(&Transform, &Player), With<Player>>,
+/// Spawns a dialog box along the bottom of the screen, starting at `left` and `width` wide (percent).
+fn spawn_dialog_box(
+    commands: &mut Commands,
+    player: Entity,
+    (left, width): (f32, f32),
+    dialog: &Dialog,
+    strings: &Strings,
 ) {
-    if !dialog_speaker_open_dialog.0 {
-        for entity in &open_dialog {
-            commands.entity(entity).despawn_recursive();
-        }
-    }
-
-    let entity = match dialog_speaker.0 {
-        Some(e) => e,
-        None => {
-            dialog_speaker_open_dialog.0 = false;
-            return;
-        }
-    };
-
-    let (_, dialog, transform) = match dialog_query.iter_mut().find(|(e, _, _)| *e == entity) {
-        Some(d) => d,
-        None => return,
-    };
-
-    let gamepad = match gamepads.iter().next() {
-        Some(gp) => gp,
-        None => Gamepad::new(0),
-    };
-    let left_stick_x = match axes.get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX)) {
-        Some(x) => x,
-        None => 0.0,
-    };
-
-    let jump_key_just_pressed = button_inputs
-        .just_pressed(GamepadButton::new(gamepad, GamepadButtonType::South))
-        || keyboard_input.just_pressed(KeyCode::Space);
-
-    let left_key_pressed = left_stick_x < -0.10 || keyboard_input.pressed(KeyCode::ArrowLeft);
-    let right_key_pressed = left_stick_x > 0.10 || keyboard_input.pressed(KeyCode::ArrowRight);
-
-    let index = if water_collection.total_player1 + water_collection.total_player2 <= 0 {
-        0
-    } else if !dialog_speaker_open_dialog.0 {
-        1
-    } else {
-        if left_key_pressed {
-            yes_or_no.0 = 0
-        }
-
-        if right_key_pressed {
-            yes_or_no.0 = 1
-        }
-
-        if yes_or_no.0 == 0 {
-            1
-        } else {
-            2
-        }
-    };
-
-    let platform_warning = if dialog.title == "Tlaloc" && (index == 1 || index == 2) {
-        let players_too_low = player_query
-            .iter()
-            .filter(|(t, _)| t.translation.y < 1110.0)
-            .collect::<Vec<_>>();
-        if players_too_low.is_empty() {
-            String::new()
-        } else {
-            players_too_low
-                .iter()
-                .fold::<String, _>(String::new(), |s, (_, player)| {
-                    if player.0 == 1 {
-                        String::from("  (Nena's drops will not contributed)")
-                    } else {
-                        String::from("  (Nata's drop will not be contributed)")
-                    }
-                })
-        }
-    } else {
-        String::new()
-    };
+    commands
+        .spawn((
+            Game,
+            DialogBox(player),
+            NodeBundle {
+                background_color: BackgroundColor(Color::srgb(0.0, 0.0, 0.0)),
+                style: Style {
+                    width: Val::Percent(width),
+                    height: Val::Percent(15.0),
+                    position_type: PositionType::Absolute,
+                    align_items: AlignItems::Center,
+                    bottom: Val::Percent(2.5),
+                    left: Val::Percent(left),
+                    ..default()
+                },
 
-    if index > 0 && !open_dialog.is_empty() {
-        time.advance_by(Duration::ZERO);
-        if right_key_pressed || left_key_pressed {
-            for entity in &open_dialog {
-                commands.entity(entity).despawn_recursive();
-            }
-            commands
-                .spawn((
-                    Game,
-                    DialogBox,
-                    NodeBundle {
-                        background_color: BackgroundColor(Color::srgb(0.0, 0.0, 0.0)),
-                        style: Style {
-                            width: Val::Percent(97.0),
-                            height: Val::Percent(15.0),
-                            position_type: PositionType::Absolute,
-                            align_items: AlignItems::Center,
-                            bottom: Val::Percent(2.5),
-                            left: Val::Percent(1.5),
-                            ..default()
+                ..default()
+            },
+        ))
+        .with_children(|child| {
+            child
+                .spawn(NodeBundle {
+                    background_color: BackgroundColor(Color::srgb(0.0, 0.0, 0.0)),
+                    style: Style {
+                        // width: Val::Percent(33.0),
+                        height: Val::Percent(50.0),
+                        position_type: PositionType::Absolute,
+                        align_items: AlignItems::Start,
+                        padding: UiRect {
+                            left: Val::Percent(1.),
+                            right: Val::Percent(1.),
+                            top: Val::Percent(1.),
+                            bottom: Val::Percent(0.),
                         },
-
+                        top: Val::Percent(-50.0),
+                        left: Val::Percent(0.0),
                         ..default()
                     },
-                ))
-                .with_children(|child| {
-                    child
-                        .spawn(NodeBundle {
-                            background_color: BackgroundColor(Color::srgb(0.0, 0.0, 0.0)),
-                            style: Style {
-                                // width: Val::Percent(33.0),
-                                height: Val::Percent(50.0),
-                                position_type: PositionType::Absolute,
-                                align_items: AlignItems::Start,
-                                padding: UiRect {
-                                    left: Val::Percent(1.),
-                                    right: Val::Percent(1.),
-                                    top: Val::Percent(1.),
-                                    bottom: Val::Percent(0.),
-                                },
-                                top: Val::Percent(-50.0),
-                                left: Val::Percent(0.0),
-                                ..default()
-                            },
 
-                            ..default()
-                        })
-                        .with_children(|child| {
-                            child.spawn((
-                                Game,
-                                TextBundle::from_section(
-                                    format!("{} ({})", dialog.title, dialog.subtitle),
-                                    dialog.dialog.sections[0].style.clone(),
-                                )
-                                .with_style(Style {
-                                    position_type: PositionType::Relative,
-                                    align_items: AlignItems::Start,
-
-                                    ..default()
-                                }),
-                            ));
-                        });
-                    child.spawn(NodeBundle {
-                        background_color: BackgroundColor(Color::srgb(0.0, 0.0, 0.0)),
-                        transform: Transform::from_translation(Vec3::new(0.0, 40.0, 0.0)),
-                        style: Style {
-                            width: Val::Percent(97.0),
-                            height: Val::Percent(15.0),
-                            position_type: PositionType::Absolute,
-                            align_items: AlignItems::Center,
-                            bottom: Val::Percent(2.5),
-                            left: Val::Percent(1.5),
-                            ..default()
-                        },
-
-                        ..default()
-                    });
-                    child.spawn((
-                        Game,
-                        ImageBundle {
-                            image: UiImage {
-                                texture: dialog.image.clone(),
-                                ..default()
-                            },
-                            style: Style {
-                                position_type: PositionType::Absolute,
-                                align_items: AlignItems::Center,
-                                top: Val::Percent(10.0),
-                                left: Val::Percent(1.0),
-                                ..default()
-                            },
-                            ..default()
-                        },
-                    ));
+                    ..default()
+                })
+                .with_children(|child| {
                     child.spawn((
                         Game,
                         TextBundle::from_section(
                             format!(
-                                "{} {}",
-                                dialog.dialog.sections[index].value.clone(),
-                                platform_warning
+                                "{} ({})",
+                                strings.get(&dialog.title),
+                                strings.get(&dialog.subtitle)
                             ),
-                            dialog.dialog.sections[0].style.clone(),
+                            dialog.style.clone(),
                         )
-                        .with_text_justify(JustifyText::Left)
                         .with_style(Style {
-                            position_type: PositionType::Absolute,
-                            align_items: AlignItems::Center,
-                            top: Val::Percent(20.5),
-                            left: Val::Px(120.0),
-                            right: Val::Px(50.0),
+                            position_type: PositionType::Relative,
+                            align_items: AlignItems::Start,
+
                             ..default()
                         }),
                     ));
                 });
-
-            return;
-        }
-        if jump_key_just_pressed {
-            if yes_or_no.0 == 1 {
-                yes_or_no.0 = 0; // Reset to no after every selection
-                if dialog.title == "Tlaloc" {
-                    let total = player_query
-                        .iter()
-                        .filter(|(t, _)| t.translation.y >= 1110.0)
-                        .fold::<u32, _>(0, |mut s, (_, player)| {
-                            if player.0 == 1 {
-                                s += water_collection.total_player1;
-                            } else {
-                                s += water_collection.total_player2;
-                            }
-                            s
-                        });
-                    total_score.0 += total;
-
-                    *game_phase = GamePhase::Reset;
-                    commands.spawn(AudioBundle {
-                        source: asset_server.load("createrain.wav"),
-                        settings: PlaybackSettings {
-                            mode: PlaybackMode::Despawn,
-                            ..default()
-                        },
-                    });
-                }
-                water_collection.total_player1 = 0;
-                water_collection.total_player2 = 0;
-            }
-            dialog_speaker_open_dialog.0 = false;
-            return;
-        }
-    } else if !open_dialog.is_empty() {
-        if jump_key_just_pressed {
-            dialog_speaker_open_dialog.0 = false;
-            return;
-        }
-        time.advance_by(Duration::ZERO);
-    } else if jump_key_just_pressed || dialog_speaker_open_dialog.0 {
-        dialog_speaker_open_dialog.0 = true;
-
-        commands
-            .spawn((
+            child.spawn((
                 Game,
-                DialogBox,
-                NodeBundle {
-                    background_color: BackgroundColor(Color::srgb(0.0, 0.0, 0.0)),
+                ImageBundle {
+                    image: UiImage {
+                        texture: dialog.image.clone(),
+                        ..default()
+                    },
                     style: Style {
-                        width: Val::Percent(97.0),
-                        height: Val::Percent(15.0),
                         position_type: PositionType::Absolute,
                         align_items: AlignItems::Center,
-                        bottom: Val::Percent(2.5),
-                        left: Val::Percent(1.5),
+                        top: Val::Percent(10.0),
+                        left: Val::Percent(1.0),
                         ..default()
                     },
-
                     ..default()
                 },
-            ))
-            .with_children(|child| {
-                child
-                    .spawn(NodeBundle {
-                        background_color: BackgroundColor(Color::srgb(0.0, 0.0, 0.0)),
-                        style: Style {
-                            // width: Val::Percent(33.0),
-                            height: Val::Percent(50.0),
-                            position_type: PositionType::Absolute,
-                            align_items: AlignItems::Start,
-                            padding: UiRect {
-                                left: Val::Percent(1.),
-                                right: Val::Percent(1.),
-                                top: Val::Percent(1.),
-                                bottom: Val::Percent(0.),
-                            },
-                            top: Val::Percent(-50.0),
-                            left: Val::Percent(0.0),
-                            ..default()
-                        },
-
-                        ..default()
-                    })
-                    .with_children(|child| {
-                        child.spawn((
-                            Game,
-                            TextBundle::from_section(
-                                format!("{} ({})", dialog.title, dialog.subtitle),
-                                dialog.dialog.sections[0].style.clone(),
-                            )
-                            .with_style(Style {
-                                position_type: PositionType::Relative,
-                                align_items: AlignItems::Start,
-
-                                ..default()
-                            }),
-                        ));
-                    });
-                child.spawn((
-                    Game,
-                    ImageBundle {
-                        image: UiImage {
-                            texture: dialog.image.clone(),
-                            ..default()
-                        },
-                        style: Style {
-                            position_type: PositionType::Absolute,
-                            align_items: AlignItems::Center,
-                            top: Val::Percent(10.0),
-                            left: Val::Percent(1.0),
-                            ..default()
-                        },
-                        ..default()
-                    },
-                ));
-                child.spawn((
-                    Game,
-                    TextBundle::from_section(
-                        format!(
-                            "{} {}",
-                            dialog.dialog.sections[index].value.clone(),
-                            platform_warning
-                        ),
-                        dialog.dialog.sections[0].style.clone(),
-                    )
+            ));
+            child.spawn((
+                Game,
+                DialogText(player),
+                TextBundle::from_section("", dialog.style.clone())
                     .with_text_justify(JustifyText::Left)
                     .with_style(Style {
                         position_type: PositionType::Absolute,
@@ -1995,44 +1860,686 @@ fn active_dialog_system(
                         right: Val::Px(50.0),
                         ..default()
                     }),
-                ));
-            });
+            ));
+        });
+}
+
+/// What dialog conditions are checked against, taken before any effect applies.
+struct DialogFacts {
+    has_water: bool,
+    all_in_range: bool,
+}
+
+impl DialogFacts {
+    fn check(&self, condition: &DialogCondition) -> bool {
+        match condition {
+            DialogCondition::HasWater => self.has_water,
+            DialogCondition::AllInRange => self.all_in_range,
+            DialogCondition::Not(condition) => !self.check(condition),
+        }
+    }
+
+    fn holds(&self, conditions: &[DialogCondition]) -> bool {
+        conditions.iter().all(|c| self.check(c))
+    }
+}
+
+fn dialog_facts(water_collection: &WaterCollection, missing: &str) -> DialogFacts {
+    DialogFacts {
+        has_water: water_collection.total_player1 + water_collection.total_player2 > 0,
+        all_in_range: missing.is_empty(),
+    }
+}
+
+fn node_body(node: &DialogNode, facts: &DialogFacts, strings: &Strings, missing: &str) -> String {
+    let mut text = strings.get(&node.text).to_string();
+    for note in node.notes.iter().filter(|n| facts.holds(&n.conditions)) {
+        text.push_str("  ");
+        text.push_str(&strings.format(&note.text, &[("missing", &missing)]));
+    }
+    text
+}
+
+fn choice_line(choices: &[(usize, String)], selection: usize) -> String {
+    choices
+        .iter()
+        .enumerate()
+        .map(|(index, (_, label))| {
+            let marker = if index == selection { "▶" } else { " " };
+            format!("{} {}", marker, label)
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Columns and rows of text that fit a dialog box `width` percent of the window wide.
+fn dialog_page_size(window: Option<&Window>, width: f32, style: &TextStyle) -> (usize, usize) {
+    // Without a window, e.g. in tests, the default resolution is assumed
+    let (window_width, window_height) = window
+        .map(|w| (w.width(), w.height()))
+        .unwrap_or((1200.0, 750.0));
+    // See the text margins in `spawn_dialog_box`, PressStart2P glyphs are square
+    let text_width = window_width * width / 100.0 - 170.0;
+    let text_height = window_height * 0.15 * (1.0 - 0.205);
+    (
+        (text_width / style.font_size) as usize,
+        (text_height / (style.font_size * 1.2)) as usize,
+    )
+}
+
+/// Shows `name` in the player's dialog box, split into pages that fit.
+fn open_dialog_node(
+    player_dialog: &mut PlayerDialog,
+    tree: &DialogTree,
+    name: &str,
+    facts: &DialogFacts,
+    strings: &Strings,
+    missing: &str,
+    (columns, rows): (usize, usize),
+) {
+    let node = &tree.nodes[name];
+    let choices = node
+        .choices
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| facts.holds(&c.conditions))
+        .map(|(index, c)| (index, strings.get(&c.label).to_string()))
+        .collect::<Vec<_>>();
+    // The choices take a blank line and a line of labels
+    let reserved = if choices.is_empty() { 0 } else { 2 };
+    let pages = paginate(
+        &node_body(node, facts, strings, missing),
+        columns,
+        rows,
+        reserved,
+    );
+    player_dialog.open(name, pages, choices);
+    player_dialog.blip = tree.blip.clone();
+}
+
+/// Tracks which players are inside each `OfferingZone`.
+fn offering_zone_system(
+    rapier_context: Res<RapierContext>,
+    mut zone_query: Query<(Entity, &mut OfferingZone)>,
+    player_query: Query<(Entity, &Player)>,
+) {
+    for (zone_entity, mut zone) in zone_query.iter_mut() {
+        let mut players = player_query
+            .iter()
+            .filter(|(e, _)| rapier_context.intersection_pair(zone_entity, *e).is_some())
+            .map(|(_, p)| p.0)
+            .collect::<Vec<_>>();
+        players.sort();
+        if zone.players != players {
+            zone.players = players;
+        }
+    }
+}
+
+fn offering_zone_label_system(
+    strings: Res<Strings>,
+    zone_query: Query<Ref<OfferingZone>>,
+    mut label_query: Query<(&Parent, &mut Text), With<OfferingZoneLabel>>,
+) {
+    for (parent, mut text) in label_query.iter_mut() {
+        let zone = match zone_query.get(parent.get()) {
+            Ok(z) => z,
+            Err(_) => continue,
+        };
+        if !zone.is_changed() && !strings.is_changed() && !text.sections[0].value.is_empty() {
+            continue;
+        }
+        let names = zone
+            .players
+            .iter()
+            .map(|p| Player(*p).name())
+            .collect::<Vec<_>>()
+            .join(" & ");
+        text.sections[0].value = if names.is_empty() {
+            strings.get("zone.empty").to_string()
+        } else {
+            strings.format("zone.in_range", &[("names", &names)])
+        };
+    }
+}
+
+fn active_dialog_system(
+    mut time: ResMut<Time>,
+    mut commands: Commands,
+    actions: Res<Actions>,
+    asset_server: Res<AssetServer>,
+    dialog_trees: Res<Assets<DialogTree>>,
+    strings: Res<Strings>,
+    mut record: RoundRecord,
+    mut streak: ResMut<OfferingStreak>,
+    mut game_phase: ResMut<GamePhase>,
+    mut active_player_query: Query<
+        (Entity, &Player, &PlayerControls, &mut PlayerDialog),
+        With<ActivePlayer>,
+    >,
+    dialog_box_query: Query<(Entity, &DialogBox)>,
+    dialog_query: Query<(&Dialog, &NpcRole)>,
+    zone_query: Query<(&Parent, &OfferingZone)>,
+    player_query: Query<&Player>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+) {
+    for (player_entity, player, controls, mut player_dialog) in active_player_query.iter_mut() {
+        let open_dialog = dialog_box_query
+            .iter()
+            .filter(|(_, b)| b.0 == player_entity)
+            .map(|(e, _)| e)
+            .collect::<Vec<_>>();
+        let close_boxes = |commands: &mut Commands| {
+            for entity in &open_dialog {
+                commands.entity(*entity).despawn_recursive();
+            }
+        };
+
+        let (speaker, dialog, role) = match player_dialog
+            .speaker
+            .and_then(|e| dialog_query.get(e).ok().map(|(d, r)| (e, d, r)))
+        {
+            Some(d) => d,
+            None => {
+                player_dialog.node = None;
+                close_boxes(&mut commands);
+                continue;
+            }
+        };
+        // Nothing to say until the dialog file has loaded
+        let tree = match dialog_trees.get(&dialog.tree) {
+            Some(t) => t,
+            None => continue,
+        };
+
+        let zone = zone_query
+            .iter()
+            .find(|(parent, _)| parent.get() == speaker)
+            .map(|(_, z)| z);
+        let in_range = |p: &Player| zone.is_none_or(|z| z.players.contains(&p.0));
+        // Everyone left out of the offering, named in the dialog's notes
+        let missing = player_query
+            .iter()
+            .filter(|p| !in_range(p))
+            .map(|p| p.name())
+            .collect::<Vec<_>>()
+            .join(" & ");
+        let facts = dialog_facts(&record.water_collection, &missing);
+
+        // Co-op splits the bottom of the screen between the two players
+        let layout = match (*record.play_mode, player.0) {
+            (PlayMode::Solo | PlayMode::Companion, _) => (1.5, 97.0),
+            (PlayMode::Coop, 1) => (1.5, 48.0),
+            (PlayMode::Coop, _) => (50.5, 48.0),
+        };
+        let page_size = dialog_page_size(window_query.get_single().ok(), layout.1, &dialog.style);
+
+        let confirm_key_just_pressed = controls.just_pressed(&actions, Action::Confirm);
+        let left_key_just_pressed = controls.just_pressed(&actions, Action::NavigateLeft);
+        let right_key_just_pressed = controls.just_pressed(&actions, Action::NavigateRight);
+
+        let node = match player_dialog.node.as_ref().and_then(|n| tree.nodes.get(n)) {
+            Some(n) => n,
+            None => {
+                player_dialog.node = None;
+                close_boxes(&mut commands);
+                if !confirm_key_just_pressed {
+                    continue;
+                }
+                if let Some(route) = tree.start.iter().find(|r| facts.holds(&r.conditions)) {
+                    open_dialog_node(
+                        &mut player_dialog,
+                        tree,
+                        &route.node,
+                        &facts,
+                        &strings,
+                        &missing,
+                        page_size,
+                    );
+                    spawn_dialog_box(&mut commands, player_entity, layout, dialog, &strings);
+                }
+                continue;
+            }
+        };
+        time.advance_by(Duration::ZERO);
+
+        if confirm_key_just_pressed {
+            // Confirm first finishes the page, then turns it
+            if !player_dialog.page_revealed() {
+                player_dialog.revealed = player_dialog.page_len();
+                continue;
+            }
+            if !player_dialog.choosing() {
+                player_dialog.page += 1;
+                player_dialog.revealed = 0;
+                continue;
+            }
+
+            let chosen = player_dialog
+                .choices
+                .get(player_dialog.selection)
+                .map(|(index, _)| &node.choices[*index]);
+            let next = match chosen {
+                Some(choice) => {
+                    for effect in &choice.effects {
+                        match effect {
+                            DialogEffect::Offer => {
+                                let water = |p: &Player| {
+                                    if p.0 == 1 {
+                                        record.water_collection.total_player1
+                                    } else {
+                                        record.water_collection.total_player2
+                                    }
+                                };
+                                let offered = player_query
+                                    .iter()
+                                    .filter(|p| in_range(p))
+                                    .map(water)
+                                    .filter(|water| *water > 0)
+                                    .collect::<Vec<_>>();
+                                let drops = offered.iter().sum::<u32>();
+                                let base = role.offering_score(drops);
+                                if role.takes_water(drops) {
+                                    // Whatever was taken without scoring is lost
+                                    for p in player_query.iter() {
+                                        if base == 0 || !in_range(p) {
+                                            record.stats.player(p).lost += water(p);
+                                        }
+                                    }
+                                }
+                                let mut score = 0;
+                                if base > 0 {
+                                    for p in player_query.iter().filter(|p| in_range(p)) {
+                                        let player_stats = record.stats.player(p);
+                                        player_stats.offerings += 1;
+                                        player_stats.biggest_offering =
+                                            player_stats.biggest_offering.max(water(p));
+                                    }
+                                    let breakdown = ScoreBreakdown::new(
+                                        base,
+                                        drops,
+                                        offered.len(),
+                                        streak.offer(),
+                                    );
+                                    score = breakdown.total();
+                                    record.total_score.0 += score;
+                                    spawn_score_popup(
+                                        &mut commands,
+                                        &asset_server,
+                                        &strings,
+                                        &breakdown,
+                                    );
+                                }
+                                if role.takes_water(drops) {
+                                    record.events.send(GameEvent::Offering {
+                                        npc: dialog.title.clone(),
+                                        role: role.clone(),
+                                        drops,
+                                        score,
+                                    });
+                                    record.water_collection.total_player1 = 0;
+                                    record.water_collection.total_player2 = 0;
+                                }
+                            }
+                            DialogEffect::StartReset => {
+                                record.events.send(GameEvent::Reset);
+                                *game_phase = GamePhase::Reset;
+                            }
+                            DialogEffect::PlaySound(path) => {
+                                commands.spawn(AudioBundle {
+                                    source: asset_server.load(path),
+                                    settings: PlaybackSettings {
+                                        mode: PlaybackMode::Despawn,
+                                        ..default()
+                                    },
+                                });
+                            }
+                        }
+                    }
+                    choice.next.clone()
+                }
+                None => node.next.clone(),
+            };
+            close_boxes(&mut commands);
+            player_dialog.node = None;
+            if let Some(next) = next.filter(|n| tree.nodes.contains_key(n)) {
+                // Effects may have changed what the next node offers
+                let facts = dialog_facts(&record.water_collection, &missing);
+                open_dialog_node(
+                    &mut player_dialog,
+                    tree,
+                    &next,
+                    &facts,
+                    &strings,
+                    &missing,
+                    page_size,
+                );
+                spawn_dialog_box(&mut commands, player_entity, layout, dialog, &strings);
+            }
+            continue;
+        }
+
+        if !player_dialog.choosing() {
+            continue;
+        }
+        if left_key_just_pressed && player_dialog.selection > 0 {
+            player_dialog.selection -= 1;
+        } else if right_key_just_pressed
+            && player_dialog.selection + 1 < player_dialog.choices.len()
+        {
+            player_dialog.selection += 1;
+        }
+    }
+}
+
+/// Reveals the dialog text a character at a time, with the choices below the last page.
+fn dialog_typewriter_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    asset_server: Res<AssetServer>,
+    mut player_dialog_query: Query<&mut PlayerDialog>,
+    mut dialog_text_query: Query<(&mut Text, &DialogText)>,
+) {
+    for (mut text, dialog_text) in dialog_text_query.iter_mut() {
+        let mut player_dialog = match player_dialog_query.get_mut(dialog_text.0) {
+            Ok(d) => d,
+            Err(_) => continue,
+        };
+        let page_len = player_dialog.page_len();
+
+        if player_dialog.revealed < page_len {
+            let before = player_dialog.revealed;
+            player_dialog.reveal.tick(time.delta());
+            let ticks = player_dialog.reveal.times_finished_this_tick() as usize;
+            player_dialog.revealed = (before + ticks).min(page_len);
+
+            if before / CHARACTERS_PER_BLIP != player_dialog.revealed / CHARACTERS_PER_BLIP {
+                if let Some(blip) = &player_dialog.blip {
+                    commands.spawn(AudioBundle {
+                        source: asset_server.load(blip),
+                        settings: PlaybackSettings {
+                            mode: PlaybackMode::Despawn,
+                            ..default()
+                        },
+                    });
+                }
+            }
+        }
+
+        let mut value = player_dialog.pages[player_dialog.page]
+            .chars()
+            .take(player_dialog.revealed)
+            .collect::<String>();
+        if player_dialog.choosing() && !player_dialog.choices.is_empty() {
+            value.push_str("\n\n");
+            value.push_str(&choice_line(
+                &player_dialog.choices,
+                player_dialog.selection,
+            ));
+        }
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
     }
 }
 
 pub struct PlatformPlugin;
 impl Plugin for PlatformPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(AppState::Game), (setup))
+        app.add_systems(OnEnter(InGame), setup.after(seed::reseed))
+            .add_event::<GameEvent>()
             .init_resource::<GamePhase>()
             .init_resource::<DevPhase>()
             .insert_resource(WaterCollection::default())
-            .insert_resource(GustTimer(Timer::from_seconds(1.0, TimerMode::Repeating)))
-            .insert_resource(DialogSpeaker::default())
-            .insert_resource(DialogSpeakerOpenDialog::default())
-            .insert_resource(DialogDecisionSelection::default())
+            .insert_resource(GustTimer(Timer::from_seconds(
+                GUST_SECONDS,
+                TimerMode::Repeating,
+            )))
+            .init_resource::<PlayMode>()
+            .init_resource::<Difficulty>()
+            .init_resource::<JumpAssist>()
+            .init_resource::<MovementModel>()
             .insert_resource(TotalTime(Timer::from_seconds(999.0, TimerMode::Once)))
             .insert_resource(TotalScore(0))
             .add_systems(PreUpdate, camera_tracking::camera_tracking_system)
-            .add_systems(Update, (time_count_system, player_indicator_system))
+            .add_systems(
+                Update,
+                (time_count_system, player_indicator_system)
+                    .in_set(GameplaySet)
+                    .run_if(in_state(AppState::Game)),
+            )
             .add_systems(
                 Update,
                 (
                     dialog_selection_system,
+                    offering_zone_system,
+                    offering_zone_label_system,
                     active_dialog_system,
+                    dialog_typewriter_system,
                     keyboard_input_system,
+                    switch_player_system,
                     resetting,
                     player_gravity_system,
+                    platform_riding_system,
                     translate_player_system,
                     cloud_movement,
-                    platform_sensor_system,
+                    drop_through_system,
+                    one_way_platform_system,
                     gust_system,
+                    gust_riding_system,
                     collectable_system,
                     // debug_system,
                 )
                     .chain()
+                    .in_set(GameplaySet)
                     .run_if(in_state(AppState::Game)),
             )
-            .add_systems(OnExit(AppState::Game), despawn_screen::<Game>);
+            .add_systems(OnExit(InGame), despawn_screen::<Game>);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::headless::{
+        active_player, headless_app, player_x, player_y, press, release, run, start_game, tap,
+        teleport,
+    };
+
+    #[test]
+    fn player_rides_a_drifting_cloud() {
+        let mut app = headless_app();
+        start_game(&mut app);
+
+        let cloud = app
+            .world_mut()
+            .spawn((
+                Game,
+                Cloud {
+                    group: 0,
+                    velocity: Vec2::new(100.0, 0.0),
+                },
+                RigidBody::Fixed,
+                Collider::cuboid(60.0, 10.0),
+                Platform {
+                    height_adjustment: 25.0,
+                },
+                CollisionGroups::new(Group::GROUP_10, Group::ALL),
+                ActiveHooks::FILTER_CONTACT_PAIRS,
+                TransformBundle::from_transform(Transform::from_xyz(210.0, -200.0, 0.0)),
+            ))
+            .id();
+        run(&mut app, 60);
+
+        let cloud_x = |app: &App| app.world().get::<Transform>(cloud).unwrap().translation.x;
+        let (player_start, cloud_start) = (player_x(&mut app, 1), cloud_x(&app));
+        run(&mut app, 60);
+        let player_moved = player_x(&mut app, 1) - player_start;
+        let cloud_moved = cloud_x(&app) - cloud_start;
+        assert!(cloud_moved < -90.0);
+        assert!((player_moved - cloud_moved).abs() < 0.1);
+    }
+
+    #[test]
+    fn down_and_jump_drops_through_a_platform() {
+        let mut app = headless_app();
+        start_game(&mut app);
+
+        // Onto the cloud platform on the left, its top is at 228.5
+        let player = active_player(&mut app);
+        teleport(&mut app, player, Vec3::new(-404.0, 300.0, 11.0));
+        run(&mut app, 60);
+        assert!(player_y(&mut app, 1) > 228.0);
+
+        press(&mut app, KeyCode::KeyS);
+        tap(&mut app, KeyCode::Space);
+        release(&mut app, KeyCode::KeyS);
+        run(&mut app, 60);
+
+        assert!(player_y(&mut app, 1) < 150.0);
+        assert!(app.world().get::<DropThrough>(player).is_none());
+        assert_eq!(app.world().resource::<RoundStats>().players[0].jumps, 0);
+    }
+
+    #[test]
+    fn platforms_judge_each_player_separately() {
+        let mut app = headless_app();
+        start_game(&mut app);
+
+        // Nena lands on the cloud platform on the left while Nata starts inside it, below its
+        // top, and falls through
+        let mut players = app.world_mut().query::<(&Player, &mut Transform)>();
+        for (player, mut transform) in players.iter_mut(app.world_mut()) {
+            let y = if player.0 == 1 { 300.0 } else { 190.0 };
+            transform.translation = Vec3::new(-404.0, y, 11.0);
+        }
+        run(&mut app, 60);
+
+        assert!(player_y(&mut app, 1) > 228.0);
+        assert!(player_y(&mut app, 2) < 100.0);
+    }
+
+    #[test]
+    fn coop_players_move_independently() {
+        let mut app = headless_app();
+        app.insert_resource(PlayMode::Coop);
+        start_game(&mut app);
+        run(&mut app, 60);
+
+        let (start1, start2) = (player_x(&mut app, 1), player_x(&mut app, 2));
+        press(&mut app, KeyCode::KeyD);
+        press(&mut app, KeyCode::ArrowLeft);
+        run(&mut app, 20);
+        release(&mut app, KeyCode::KeyD);
+        release(&mut app, KeyCode::ArrowLeft);
+
+        assert!(player_x(&mut app, 1) > start1);
+        assert!(player_x(&mut app, 2) < start2);
+    }
+
+    /// Jumps made after pressing Space in the air just above the ground.
+    fn jumps_pressed_before_landing(buffer_seconds: f32) -> u32 {
+        let mut app = headless_app();
+        // Without coyote time, so the press can't count as a late jump off the ground
+        app.insert_resource(JumpAssist {
+            coyote_seconds: 0.0,
+            buffer_seconds,
+        });
+        start_game(&mut app);
+        run(&mut app, 180);
+
+        let player = active_player(&mut app);
+        app.world_mut()
+            .get_mut::<Transform>(player)
+            .unwrap()
+            .translation
+            .y += 40.0;
+        // The controller sees the new position a frame late, on the first it is still grounded
+        run(&mut app, 2);
+        tap(&mut app, KeyCode::Space);
+        run(&mut app, 30);
+        app.world().resource::<RoundStats>().players[0].jumps
+    }
+
+    #[test]
+    fn jump_pressed_in_the_air_starts_on_landing() {
+        assert_eq!(jumps_pressed_before_landing(0.0), 0);
+        assert_eq!(jumps_pressed_before_landing(1.0), 1);
+    }
+
+    /// Jumps made when pressing Space `frames` frames after walking off a platform.
+    fn jumps_after_walking_off(frames: usize) -> u32 {
+        let mut app = headless_app();
+        // Without a jump buffer, so the press can't count as an early jump before landing
+        app.insert_resource(JumpAssist {
+            coyote_seconds: 0.1,
+            buffer_seconds: 0.0,
+        });
+        start_game(&mut app);
+
+        // The right hand platform spans 208 to 600
+        let player = active_player(&mut app);
+        teleport(&mut app, player, Vec3::new(230.0, 560.0, 11.0));
+        run(&mut app, 60);
+        let grounded = |app: &App| {
+            app.world()
+                .get::<KinematicCharacterControllerOutput>(player)
+                .is_some_and(|output| output.grounded)
+        };
+        assert!(grounded(&app));
+
+        press(&mut app, KeyCode::KeyA);
+        for _ in 0..60 {
+            if !grounded(&app) {
+                break;
+            }
+            app.update();
+        }
+        assert!(!grounded(&app));
+        run(&mut app, frames);
+        tap(&mut app, KeyCode::Space);
+        release(&mut app, KeyCode::KeyA);
+        run(&mut app, 5);
+        app.world().resource::<RoundStats>().players[0].jumps
+    }
+
+    #[test]
+    fn jump_just_after_walking_off_a_platform_still_starts() {
+        assert_eq!(jumps_after_walking_off(1), 1);
+    }
+
+    #[test]
+    fn jump_long_after_walking_off_a_platform_does_not_start() {
+        assert_eq!(jumps_after_walking_off(20), 0);
+    }
+
+    /// Summed length of the drop spawners' ranges and speed of the fastest cloud as spawned.
+    fn spawners(difficulty: Difficulty) -> (i32, f32) {
+        let mut app = headless_app();
+        app.insert_resource(difficulty);
+        start_game(&mut app);
+        let drops = app
+            .world_mut()
+            .query::<&WaterCollectableSpawner>()
+            .iter(app.world())
+            .map(|s| s.probability.len() as i32)
+            .sum();
+        let clouds = app
+            .world_mut()
+            .query::<&CloudSpawner>()
+            .iter(app.world())
+            .map(|s| s.max_velocity.x)
+            .fold(0.0, f32::max);
+        (drops, clouds)
+    }
+
+    #[test]
+    fn difficulty_reshapes_spawners() {
+        let (easy_drops, easy_clouds) = spawners(Difficulty::Easy);
+        let (normal_drops, normal_clouds) = spawners(Difficulty::Normal);
+        let (hard_drops, hard_clouds) = spawners(Difficulty::Hard);
+        // A longer range makes rolling a 1 less likely
+        assert!(easy_drops < normal_drops && normal_drops < hard_drops);
+        assert!(easy_clouds < normal_clouds && normal_clouds < hard_clouds);
     }
 }
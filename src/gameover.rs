@@ -1,6 +1,15 @@
 use crate::despawn_screen;
-use crate::game::TotalScore;
+use crate::game::{Difficulty, TotalScore};
+use crate::highscores::{
+    table_text, HighScoreBackend, HighScoreEntry, HighScores, LastRecord, NAME_LENGTH,
+};
+use crate::input::{Action, Actions};
+use crate::locale::Strings;
+use crate::menu::TotalSeconds;
+use crate::seed::RunSeed;
+use crate::stats::RoundStats;
 use crate::AppState;
+use bevy::ecs::system::SystemParam;
 use bevy::{prelude::*, render::view::RenderLayers};
 
 #[derive(Component)]
@@ -9,12 +18,56 @@ pub struct OnGameOverScreen;
 #[derive(Resource, Deref, DerefMut)]
 struct GameOverTimer(Timer);
 
+/// Name being typed for a new record, one letter at a time.
+#[derive(Component)]
+struct NameEntry {
+    rate_limit_selection: Timer,
+    letters: [u8; NAME_LENGTH],
+    cursor: usize,
+}
+
+impl NameEntry {
+    fn name(&self) -> String {
+        self.letters.iter().map(|l| *l as char).collect()
+    }
+
+    fn text(&self, strings: &Strings) -> String {
+        let letters = self
+            .letters
+            .iter()
+            .enumerate()
+            .map(|(index, l)| {
+                if index == self.cursor {
+                    format!("[{}]", *l as char)
+                } else {
+                    format!(" {} ", *l as char)
+                }
+            })
+            .collect::<String>();
+        format!(
+            "{}\n\n{}\n\n{}",
+            strings.get("gameover.new_record"),
+            strings.get("gameover.enter_name"),
+            letters
+        )
+    }
+}
+
 pub struct GameOverPlugin;
 
 impl Plugin for GameOverPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(AppState::GameOver), gameover_setup)
-            .add_systems(Update, countdown.run_if(in_state(AppState::GameOver)))
+            .add_systems(
+                Update,
+                (
+                    countdown,
+                    // Keys still held from the round must not type or save the name
+                    name_entry_system.run_if(game_over_timer_finished),
+                )
+                    .chain()
+                    .run_if(in_state(AppState::GameOver)),
+            )
             .add_systems(
                 OnExit(AppState::GameOver),
                 despawn_screen::<OnGameOverScreen>,
@@ -25,13 +78,61 @@ impl Plugin for GameOverPlugin {
 #[derive(Component)]
 struct GameOverCamera;
 
+/// How the round ended and what it was played with.
+#[derive(SystemParam)]
+struct FinalResult<'w> {
+    total_score: Res<'w, TotalScore>,
+    total_seconds: Res<'w, TotalSeconds>,
+    run_seed: Res<'w, RunSeed>,
+    difficulty: Res<'w, Difficulty>,
+    round_stats: Res<'w, RoundStats>,
+}
+
+impl FinalResult<'_> {
+    fn seconds(&self) -> u32 {
+        self.total_seconds.0 as u32
+    }
+}
+
+/// The saved high-score tables and the record just set, if any.
+#[derive(SystemParam)]
+struct Records<'w> {
+    backend: Res<'w, HighScoreBackend>,
+    high_scores: ResMut<'w, HighScores>,
+    last_record: ResMut<'w, LastRecord>,
+}
+
+impl Records<'_> {
+    fn text(&self, strings: &Strings, seconds: u32) -> String {
+        format!(
+            "{}\n\n{}",
+            strings.format("highscores.best_of", &[("seconds", &seconds)]),
+            table_text(strings, &self.high_scores, &self.last_record, seconds)
+        )
+    }
+
+    /// Adds `entry` to the table for `seconds`, saves the tables and remembers its rank.
+    fn add(&mut self, seconds: u32, entry: HighScoreEntry) {
+        let rank = self.high_scores.insert(seconds, entry);
+        self.high_scores.save(&self.backend);
+        self.last_record.0 = rank.map(|rank| (seconds, rank));
+    }
+}
+
+/// What each character did during the round, see `RoundStats`.
+#[derive(Component)]
+pub struct RoundSummary;
+
 fn gameover_setup(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     mut bg: ResMut<ClearColor>,
-    total_score: Res<TotalScore>,
+    result: FinalResult,
+    mut records: Records,
+    strings: Res<Strings>,
 ) {
     bg.0 = Color::BLACK;
+    records.last_record.0 = None;
 
     commands.spawn((
         OnGameOverScreen,
@@ -52,7 +153,7 @@ fn gameover_setup(
         OnGameOverScreen,
         TextBundle::from_sections(vec![
             TextSection {
-                value: String::from("Final Score:\n\n"),
+                value: format!("{}\n\n", strings.get("gameover.final_score")),
                 style: TextStyle {
                     font: asset_server.load("fonts/PressStart2P-vaV7.ttf"),
                     font_size: 52.0,
@@ -61,7 +162,7 @@ fn gameover_setup(
                 },
             },
             TextSection {
-                value: total_score.to_string(),
+                value: result.total_score.to_string(),
                 style: TextStyle {
                     font: asset_server.load("fonts/PressStart2P-vaV7.ttf"),
                     font_size: 52.0,
@@ -69,25 +170,65 @@ fn gameover_setup(
                     ..default()
                 },
             },
+            TextSection {
+                value: format!(
+                    "\n\n{}",
+                    strings.format(
+                        "gameover.seed",
+                        &[
+                            ("seed", &result.run_seed.seed),
+                            ("mode", &strings.get(&result.run_seed.mode.label())),
+                        ],
+                    )
+                ),
+                style: TextStyle {
+                    font: asset_server.load("fonts/PressStart2P-vaV7.ttf"),
+                    font_size: 16.0,
+                    color: Color::WHITE,
+                },
+            },
         ])
         .with_text_justify(JustifyText::Center)
         .with_style(Style {
             position_type: PositionType::Absolute,
             align_items: AlignItems::Center,
-            top: Val::Percent(20.0),
+            top: Val::Percent(5.0),
             left: Val::Percent(20.0),
 
             ..default()
         }),
     ));
 
+    commands.spawn((
+        RenderLayers::layer(2),
+        OnGameOverScreen,
+        RoundSummary,
+        TextBundle::from_section(
+            result.round_stats.summary_text(&strings),
+            TextStyle {
+                font: asset_server.load("fonts/PressStart2P-vaV7.ttf"),
+                font_size: 12.0,
+                color: Color::WHITE,
+            },
+        )
+        .with_text_justify(JustifyText::Left)
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Percent(34.0),
+            left: Val::Percent(20.0),
+            ..default()
+        }),
+    ));
+
     commands.spawn((
         RenderLayers::layer(2),
         OnGameOverScreen,
         TextBundle::from_sections(vec![
             TextSection {
-                value: String::from(
-                    "Credits\n\nPlay testers\n\n- Julien, Frankie & Dominic Aguilar\n\n",
+                value: format!(
+                    "{}\n\n{}\n\n- Julien, Frankie & Dominic Aguilar\n\n",
+                    strings.get("credits.title"),
+                    strings.get("credits.play_testers")
                 ),
                 style: TextStyle {
                     font: asset_server.load("fonts/PressStart2P-vaV7.ttf"),
@@ -97,9 +238,7 @@ fn gameover_setup(
                 },
             },
             TextSection {
-                value: String::from(
-                    "Lead Engineer, Music Producer, Art & Design\n\n- Isa Aguilar\n\n",
-                ),
+                value: format!("{}\n\n- Isa Aguilar\n\n", strings.get("credits.roles")),
                 style: TextStyle {
                     font: asset_server.load("fonts/PressStart2P-vaV7.ttf"),
                     font_size: 24.0,
@@ -108,7 +247,7 @@ fn gameover_setup(
                 },
             },
             TextSection {
-                value: String::from("Special thanks to ♥♥ Alma Aguilar (Wifey!) ♥♥"),
+                value: strings.get("credits.thanks").to_string(),
                 style: TextStyle {
                     font: asset_server.load("fonts/PressStart2P-vaV7.ttf"),
                     font_size: 24.0,
@@ -128,28 +267,169 @@ fn gameover_setup(
         }),
     ));
 
+    let seconds = result.seconds();
+    let name_entry = records
+        .high_scores
+        .qualifies(seconds, result.total_score.0)
+        .then(|| NameEntry {
+            rate_limit_selection: Timer::from_seconds(0.2, TimerMode::Once),
+            letters: [b'A'; NAME_LENGTH],
+            cursor: 0,
+        });
+    let records_value = match &name_entry {
+        Some(entry) => entry.text(&strings),
+        None => records.text(&strings, seconds),
+    };
+    let mut records = commands.spawn((
+        RenderLayers::layer(2),
+        OnGameOverScreen,
+        TextBundle::from_section(
+            records_value,
+            TextStyle {
+                font: asset_server.load("fonts/PressStart2P-vaV7.ttf"),
+                font_size: 12.0,
+                color: Color::WHITE,
+            },
+        )
+        .with_text_justify(JustifyText::Left)
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Percent(4.0),
+            left: Val::Percent(74.0),
+            ..default()
+        }),
+    ));
+    if let Some(entry) = name_entry {
+        records.insert(entry);
+    }
+
     commands.insert_resource(GameOverTimer(Timer::from_seconds(1.0, TimerMode::Once)));
 }
 
+/// Up and down pick a letter, left and right move between letters, confirm on the last one saves.
+fn name_entry_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    actions: Res<Actions>,
+    result: FinalResult,
+    mut records: Records,
+    strings: Res<Strings>,
+    mut name_entry_query: Query<(Entity, &mut Text, &mut NameEntry)>,
+) {
+    let (entity, mut text, mut entry) = match name_entry_query.get_single_mut() {
+        Ok(q) => q,
+        Err(_) => return,
+    };
+    entry.rate_limit_selection.tick(time.delta());
+
+    if actions.any_just_pressed(Action::Confirm) {
+        if entry.cursor + 1 < NAME_LENGTH {
+            entry.cursor += 1;
+        } else {
+            let seconds = result.seconds();
+            records.add(
+                seconds,
+                HighScoreEntry {
+                    name: entry.name(),
+                    score: result.total_score.0,
+                    seed: result.run_seed.seed,
+                    difficulty: *result.difficulty,
+                },
+            );
+            text.sections[0].value = records.text(&strings, seconds);
+            commands.entity(entity).remove::<NameEntry>();
+            return;
+        }
+    } else if entry.rate_limit_selection.finished() {
+        let cursor = entry.cursor;
+        if actions.any_pressed(Action::NavigateUp) {
+            entry.letters[cursor] = if entry.letters[cursor] == b'Z' {
+                b'A'
+            } else {
+                entry.letters[cursor] + 1
+            };
+        } else if actions.any_pressed(Action::NavigateDown) {
+            entry.letters[cursor] = if entry.letters[cursor] == b'A' {
+                b'Z'
+            } else {
+                entry.letters[cursor] - 1
+            };
+        } else if actions.any_pressed(Action::NavigateLeft) && cursor > 0 {
+            entry.cursor -= 1;
+        } else if actions.any_pressed(Action::NavigateRight) && cursor + 1 < NAME_LENGTH {
+            entry.cursor += 1;
+        } else {
+            return;
+        }
+        entry.rate_limit_selection.reset();
+    } else {
+        return;
+    }
+    text.sections[0].value = entry.text(&strings);
+}
+
+/// Run conditions are all evaluated, so this one also runs before the first game over.
+fn game_over_timer_finished(timer: Option<Res<GameOverTimer>>) -> bool {
+    timer.is_some_and(|t| t.finished())
+}
+
 fn countdown(
     mut app_state: ResMut<NextState<AppState>>,
     time: Res<Time>,
     mut timer: ResMut<GameOverTimer>,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    gamepads: Res<Gamepads>,
-    button_inputs: Res<ButtonInput<GamepadButton>>,
+    actions: Res<Actions>,
+    name_entry_query: Query<(), With<NameEntry>>,
 ) {
-    if timer.tick(time.delta()).finished() {
-        let gamepad = match gamepads.iter().next() {
-            Some(gp) => gp,
-            None => Gamepad::new(0),
-        };
-        let jump_key_just_pressed = button_inputs
-            .just_pressed(GamepadButton::new(gamepad, GamepadButtonType::South))
-            || keyboard_input.just_pressed(KeyCode::Space);
-
-        if jump_key_just_pressed {
-            app_state.set(AppState::Menu);
+    let finished = timer.tick(time.delta()).finished();
+    // Confirm types the name until it is saved
+    if !name_entry_query.is_empty() {
+        return;
+    }
+    if finished && actions.any_just_pressed(Action::Confirm) {
+        app_state.set(AppState::Menu);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::headless::{headless_app, run, start_game, state, tap};
+    use crate::settings::Settings;
+
+    #[test]
+    fn new_record_is_named_and_saved() {
+        let mut app = headless_app();
+        app.world_mut().resource_mut::<Settings>().total_seconds = 1.0;
+        app.insert_resource(Difficulty::Hard);
+        start_game(&mut app);
+        app.world_mut().resource_mut::<TotalScore>().0 = 42;
+        run(&mut app, 70);
+        assert_eq!(state(&app), AppState::GameOver);
+        // Confirm mashed as the round ends saves nothing
+        for _ in 0..3 {
+            tap(&mut app, KeyCode::Space);
         }
+        assert!(app.world().resource::<HighScores>().table(1).is_empty());
+        run(&mut app, 60);
+
+        // "BAC": up on the first letter, then up twice on the last
+        tap(&mut app, KeyCode::KeyW);
+        tap(&mut app, KeyCode::Space);
+        tap(&mut app, KeyCode::Space);
+        run(&mut app, 15);
+        tap(&mut app, KeyCode::KeyW);
+        run(&mut app, 15);
+        tap(&mut app, KeyCode::KeyW);
+        tap(&mut app, KeyCode::Space);
+
+        let table = app.world().resource::<HighScores>().table(1).to_vec();
+        assert_eq!(table.len(), 1);
+        assert_eq!(table[0].name, "BAC");
+        assert_eq!(table[0].score, 42);
+        assert_eq!(table[0].difficulty, Difficulty::Hard);
+        assert_eq!(app.world().resource::<LastRecord>().0, Some((1, 0)));
+        // Still on the results until confirmed again
+        run(&mut app, 2);
+        assert_eq!(state(&app), AppState::GameOver);
     }
 }
@@ -0,0 +1,268 @@
+use crate::achievements::AchievementBackend;
+use crate::game::{
+    ActivePlayer, Player, PlayerDialog, TotalScore, WaterCollectable, WaterCollection,
+};
+use crate::highscores::HighScoreBackend;
+use crate::level::CurrentLevel;
+use crate::settings::{Settings, SettingsBackend};
+use crate::storage::MemoryStorage;
+use crate::{game, AppState, GamePlugins};
+use bevy::audio::AudioSource;
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::{ButtonState, InputPlugin};
+use bevy::prelude::*;
+use bevy::state::app::StatesPlugin;
+use bevy::time::TimeUpdateStrategy;
+use bevy_rapier2d::prelude::*;
+use std::time::Duration;
+
+/// Frame length used by the headless app so every run advances the same way.
+pub const TICK: Duration = Duration::from_micros(16_667);
+
+/// Stand-in for `setup::WindowSetup` without a window, rendering or audio output.
+pub struct HeadlessSetup;
+
+impl Plugin for HeadlessSetup {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin::default(),
+            StatesPlugin,
+            InputPlugin,
+            TransformPlugin,
+            HierarchyPlugin,
+        ))
+        // Handles are still created for these even though nothing renders or plays them
+        .init_asset::<Image>()
+        .init_asset::<TextureAtlasLayout>()
+        .init_asset::<Font>()
+        .init_asset::<AudioSource>()
+        .init_resource::<GlobalVolume>()
+        .insert_resource(TimeUpdateStrategy::ManualDuration(TICK))
+        .insert_resource(ClearColor(Color::BLACK));
+    }
+}
+
+/// Builds the game without a window, ready to be stepped with `App::update`.
+pub fn headless_app() -> App {
+    let mut app = App::new();
+    app.add_plugins(HeadlessSetup)
+        // Tests never touch the settings, high score or achievement files
+        .insert_resource(SettingsBackend(Box::new(MemoryStorage::default())))
+        .insert_resource(HighScoreBackend(Box::new(MemoryStorage::default())))
+        .insert_resource(AchievementBackend(Box::new(MemoryStorage::default())))
+        .add_plugins(GamePlugins);
+    app
+}
+
+pub fn run(app: &mut App, ticks: usize) {
+    for _ in 0..ticks {
+        app.update();
+    }
+}
+
+pub fn state(app: &App) -> AppState {
+    *app.world().resource::<State<AppState>>().get()
+}
+
+/// Waits for the level file and its dialogs to load.
+pub fn load_level(app: &mut App) {
+    for _ in 0..1000 {
+        app.update();
+        let handle = app.world().resource::<CurrentLevel>().handle.clone();
+        if app
+            .world()
+            .resource::<AssetServer>()
+            .is_loaded_with_dependencies(&handle)
+        {
+            return;
+        }
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    panic!("level did not load");
+}
+
+/// Loads the level, then enters `AppState::Game`.
+pub fn start_game(app: &mut App) {
+    load_level(app);
+    app.world_mut()
+        .resource_mut::<NextState<AppState>>()
+        .set(AppState::Game);
+    run(app, 2);
+}
+
+fn logical_key(key_code: KeyCode) -> Key {
+    match key_code {
+        KeyCode::Space => Key::Space,
+        KeyCode::ArrowLeft => Key::ArrowLeft,
+        KeyCode::ArrowRight => Key::ArrowRight,
+        KeyCode::ArrowUp => Key::ArrowUp,
+        KeyCode::ArrowDown => Key::ArrowDown,
+        // KeyA is "a"
+        _ => Key::Character(
+            format!("{:?}", key_code)
+                .trim_start_matches("Key")
+                .to_lowercase()
+                .into(),
+        ),
+    }
+}
+
+fn key(app: &mut App, key_code: KeyCode, state: ButtonState) {
+    app.world_mut().send_event(KeyboardInput {
+        key_code,
+        logical_key: logical_key(key_code),
+        state,
+        window: Entity::PLACEHOLDER,
+    });
+    app.update();
+}
+
+pub fn press(app: &mut App, key_code: KeyCode) {
+    key(app, key_code, ButtonState::Pressed);
+}
+
+pub fn release(app: &mut App, key_code: KeyCode) {
+    key(app, key_code, ButtonState::Released);
+}
+
+pub fn tap(app: &mut App, key_code: KeyCode) {
+    press(app, key_code);
+    release(app, key_code);
+}
+
+/// Presses `key_code`, runs `ticks` frames and lets go.
+pub fn hold(app: &mut App, key_code: KeyCode, ticks: usize) {
+    press(app, key_code);
+    run(app, ticks);
+    release(app, key_code);
+}
+
+pub fn player_x(app: &mut App, number: usize) -> f32 {
+    app.world_mut()
+        .query::<(&Player, &Transform)>()
+        .iter(app.world())
+        .find(|(p, _)| p.0 == number)
+        .map(|(_, t)| t.translation.x)
+        .unwrap()
+}
+
+pub fn player_y(app: &mut App, number: usize) -> f32 {
+    app.world_mut()
+        .query::<(&Player, &Transform)>()
+        .iter(app.world())
+        .find(|(p, _)| p.0 == number)
+        .map(|(_, t)| t.translation.y)
+        .unwrap()
+}
+
+pub fn active_player(app: &mut App) -> Entity {
+    app.world_mut()
+        .query_filtered::<Entity, With<ActivePlayer>>()
+        .single(app.world())
+}
+
+pub fn teleport(app: &mut App, entity: Entity, translation: Vec3) {
+    app.world_mut()
+        .get_mut::<Transform>(entity)
+        .unwrap()
+        .translation = translation;
+}
+
+/// A drop that can be picked up at `translation`.
+pub fn spawn_drop(app: &mut App, translation: Vec3) -> Entity {
+    app.world_mut()
+        .spawn((
+            game::Game,
+            WaterCollectable(Timer::from_seconds(30.0, TimerMode::Once)),
+            Sensor,
+            Collider::cuboid(10., 10.0),
+            TransformBundle::from_transform(Transform::from_translation(translation)),
+        ))
+        .id()
+}
+
+/// Puts the active character next to Chalchiuhtlicue, the river goddess on the ground.
+pub fn visit_chalchiuhtlicue(app: &mut App) {
+    let player = active_player(app);
+    teleport(app, player, Vec3::new(-450.0, -650.0, 11.0));
+    run(app, 5);
+}
+
+/// Hands whatever the active character carries to Chalchiuhtlicue: reads her intro, picks "Yes"
+/// and confirms.
+pub fn offer_at_chalchiuhtlicue(app: &mut App) {
+    visit_chalchiuhtlicue(app);
+    tap(app, KeyCode::Space);
+    tap(app, KeyCode::Space);
+    tap(app, KeyCode::ArrowRight);
+    tap(app, KeyCode::Space);
+}
+
+/// Puts Nena next to Tlaloc carrying `water` drops and offers them: opens the dialog, reads
+/// every page, picks "Yes" and confirms.
+pub fn offer_at_tlaloc(app: &mut App, water: u32) {
+    reach_tlaloc_choice(app, water);
+    tap(app, KeyCode::ArrowRight);
+    tap(app, KeyCode::Space);
+}
+
+/// Like `offer_at_tlaloc` but stops at the choice, while Nena is still in the offering zone.
+pub fn reach_tlaloc_choice(app: &mut App, water: u32) {
+    let player = active_player(app);
+    assert_eq!(app.world().get::<Player>(player).unwrap().0, 1);
+    teleport(app, player, Vec3::new(0.0, 1205.0, 11.0));
+    app.world_mut()
+        .resource_mut::<WaterCollection>()
+        .total_player1 = water;
+    run(app, 5);
+
+    // A note about whoever is out of range adds a page
+    for _ in 0..10 {
+        let dialog = app.world().get::<PlayerDialog>(player).unwrap();
+        if dialog.is_open() && dialog.choosing() {
+            break;
+        }
+        tap(app, KeyCode::Space);
+    }
+}
+
+#[test]
+fn collects_drop_touching_player() {
+    let mut app = headless_app();
+    start_game(&mut app);
+
+    let player = active_player(&mut app);
+    let position = app.world().get::<Transform>(player).unwrap().translation;
+    spawn_drop(&mut app, position);
+    run(&mut app, 3);
+
+    let water_collection = app.world().resource::<WaterCollection>();
+    assert_eq!(water_collection.total_player1, 1);
+    assert_eq!(water_collection.total_player2, 0);
+}
+
+#[test]
+fn offering_to_tlaloc_scores_water() {
+    let mut app = headless_app();
+    start_game(&mut app);
+
+    offer_at_tlaloc(&mut app, 5);
+
+    assert_eq!(app.world().resource::<TotalScore>().0, 5);
+    assert_eq!(app.world().resource::<WaterCollection>().total_player1, 0);
+}
+
+#[test]
+fn game_ends_when_time_runs_out() {
+    let mut app = headless_app();
+    // Settings are loaded at startup and pick the game length
+    app.world_mut().resource_mut::<Settings>().total_seconds = 1.0;
+    start_game(&mut app);
+
+    run(&mut app, 30);
+    assert_eq!(state(&app), AppState::Game);
+
+    run(&mut app, 40);
+    assert_eq!(state(&app), AppState::GameOver);
+}
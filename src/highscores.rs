@@ -0,0 +1,283 @@
+use crate::despawn_screen;
+use crate::game::Difficulty;
+use crate::input::{Action, Actions};
+use crate::locale::Strings;
+use crate::menu::TotalSeconds;
+use crate::storage::Storage;
+use crate::AppState;
+use bevy::{prelude::*, render::view::RenderLayers};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+const DEFAULT_HIGH_SCORES_PATH: &str = "highscores.ron";
+
+/// Entries kept per game length.
+pub const TABLE_SIZE: usize = 10;
+
+/// Game lengths offered in the menu, each with its own table.
+pub const GAME_LENGTHS: [u32; 3] = [180, 240, 300];
+
+pub const NAME_LENGTH: usize = 3;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HighScoreEntry {
+    pub name: String,
+    pub score: u32,
+    pub seed: u64,
+    /// Entries from before difficulty presets were all played on Normal
+    #[serde(default)]
+    pub difficulty: Difficulty,
+}
+
+/// Best scores for every game length, highest first.
+#[derive(Resource, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HighScores {
+    /// Keyed by game length in seconds
+    pub tables: BTreeMap<u32, Vec<HighScoreEntry>>,
+}
+
+impl HighScores {
+    pub fn table(&self, seconds: u32) -> &[HighScoreEntry] {
+        self.tables.get(&seconds).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Whether `score` would make it into the table for `seconds`.
+    pub fn qualifies(&self, seconds: u32, score: u32) -> bool {
+        let table = self.table(seconds);
+        score > 0
+            && (table.len() < TABLE_SIZE || table.last().is_some_and(|last| score > last.score))
+    }
+
+    /// Adds `entry` below any equal score and returns its rank, or `None` if it did not fit.
+    pub fn insert(&mut self, seconds: u32, entry: HighScoreEntry) -> Option<usize> {
+        let table = self.tables.entry(seconds).or_default();
+        let rank = table.partition_point(|e| e.score >= entry.score);
+        if rank >= TABLE_SIZE {
+            return None;
+        }
+        table.insert(rank, entry);
+        table.truncate(TABLE_SIZE);
+        Some(rank)
+    }
+
+    pub fn save(&self, backend: &HighScoreBackend) {
+        let result = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| e.to_string())
+            .and_then(|s| backend.0.save(&s));
+        match result {
+            Ok(_) => info!("Saved high scores"),
+            Err(e) => error!("Could not save high scores: {}", e),
+        }
+    }
+}
+
+/// Where high scores are kept, `highscores.ron` unless `--highscores <path>` is given.
+#[derive(Resource)]
+pub struct HighScoreBackend(pub Box<dyn Storage>);
+
+impl Default for HighScoreBackend {
+    #[cfg(not(target_arch = "wasm32"))]
+    fn default() -> Self {
+        HighScoreBackend(Box::new(crate::storage::FileStorage {
+            path: crate::launch_arg("--highscores")
+                .unwrap_or_else(|| String::from(DEFAULT_HIGH_SCORES_PATH)),
+        }))
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn default() -> Self {
+        HighScoreBackend(Box::new(crate::storage::MemoryStorage::default()))
+    }
+}
+
+/// Game length and rank of the record set in the last game, highlighted in the tables.
+#[derive(Resource, Default)]
+pub struct LastRecord(pub Option<(u32, usize)>);
+
+/// Lines of the table for `seconds` with the difficulty of each, `▶` in front of the last record.
+pub fn table_text(
+    strings: &Strings,
+    high_scores: &HighScores,
+    last_record: &LastRecord,
+    seconds: u32,
+) -> String {
+    let table = high_scores.table(seconds);
+    if table.is_empty() {
+        return format!("  {}\n", strings.get("highscores.empty"));
+    }
+    table
+        .iter()
+        .enumerate()
+        .map(|(rank, entry)| {
+            let marker = if last_record.0 == Some((seconds, rank)) {
+                "▶"
+            } else {
+                " "
+            };
+            format!(
+                "{}{:>2}. {} {:>5}  {}\n\n",
+                marker,
+                rank + 1,
+                entry.name,
+                entry.score,
+                strings.get(entry.difficulty.label())
+            )
+        })
+        .collect()
+}
+
+#[derive(Component)]
+pub struct OnHighScoresScreen;
+
+#[derive(Component)]
+struct HighScoresView {
+    rate_limit_selection: Timer,
+    /// Index into `GAME_LENGTHS`
+    length_index: usize,
+}
+
+pub struct HighScoresPlugin;
+
+impl Plugin for HighScoresPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HighScoreBackend>()
+            .init_resource::<HighScores>()
+            .init_resource::<LastRecord>()
+            .add_systems(PreStartup, load_high_scores)
+            .add_systems(OnEnter(AppState::HighScores), high_scores_setup)
+            .add_systems(
+                Update,
+                high_scores_system.run_if(in_state(AppState::HighScores)),
+            )
+            .add_systems(
+                OnExit(AppState::HighScores),
+                despawn_screen::<OnHighScoresScreen>,
+            );
+    }
+}
+
+fn load_high_scores(backend: Res<HighScoreBackend>, mut high_scores: ResMut<HighScores>) {
+    match backend.0.load() {
+        Ok(Some(contents)) => match ron::de::from_str::<HighScores>(&contents) {
+            Ok(h) => {
+                info!("Loaded high scores");
+                *high_scores = h;
+            }
+            Err(e) => error!("Could not parse high scores: {}", e),
+        },
+        Ok(None) => {}
+        Err(e) => error!("Could not read high scores: {}", e),
+    }
+}
+
+#[derive(Component)]
+struct HighScoresCamera;
+
+fn high_scores_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut bg: ResMut<ClearColor>,
+    high_scores: Res<HighScores>,
+    last_record: Res<LastRecord>,
+    total_seconds: Res<TotalSeconds>,
+    strings: Res<Strings>,
+) {
+    bg.0 = Color::srgb(0.7, 0.7, 0.7);
+
+    commands.spawn((
+        OnHighScoresScreen,
+        HighScoresCamera,
+        Camera2dBundle {
+            camera: Camera {
+                order: 1,
+                ..default()
+            },
+            ..default()
+        },
+        RenderLayers::from_layers(&[2, 3]),
+    ));
+
+    let length_index = GAME_LENGTHS
+        .iter()
+        .position(|s| *s == total_seconds.0 as u32)
+        .unwrap_or(0);
+    let style = TextStyle {
+        font: asset_server.load("fonts/PressStart2P-vaV7.ttf"),
+        font_size: 18.0,
+        color: Color::BLACK,
+    };
+    commands.spawn((
+        OnHighScoresScreen,
+        RenderLayers::layer(2),
+        HighScoresView {
+            rate_limit_selection: Timer::from_seconds(0.2, TimerMode::Once),
+            length_index,
+        },
+        TextBundle::from_sections(vec![
+            TextSection {
+                value: heading(&strings, GAME_LENGTHS[length_index]),
+                style: style.clone(),
+            },
+            TextSection {
+                value: table_text(
+                    &strings,
+                    &high_scores,
+                    &last_record,
+                    GAME_LENGTHS[length_index],
+                ),
+                style,
+            },
+        ])
+        .with_text_justify(JustifyText::Left)
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Percent(8.0),
+            left: Val::Percent(20.0),
+            ..default()
+        }),
+    ));
+}
+
+fn heading(strings: &Strings, seconds: u32) -> String {
+    format!(
+        "{}\n\n",
+        strings.format("highscores.heading", &[("seconds", &seconds)])
+    )
+}
+
+fn high_scores_system(
+    time: Res<Time>,
+    mut app_state: ResMut<NextState<AppState>>,
+    high_scores: Res<HighScores>,
+    last_record: Res<LastRecord>,
+    strings: Res<Strings>,
+    actions: Res<Actions>,
+    mut view_query: Query<(&mut Text, &mut HighScoresView)>,
+) {
+    let (mut text, mut view) = match view_query.get_single_mut() {
+        Ok(q) => q,
+        Err(_) => return,
+    };
+    view.rate_limit_selection.tick(time.delta());
+
+    if actions.any_just_pressed(Action::Confirm) || actions.any_just_pressed(Action::Pause) {
+        app_state.set(AppState::Menu);
+        return;
+    }
+
+    if !view.rate_limit_selection.finished() {
+        return;
+    }
+    if actions.any_pressed(Action::NavigateLeft) {
+        view.length_index = (view.length_index + GAME_LENGTHS.len() - 1) % GAME_LENGTHS.len();
+    } else if actions.any_pressed(Action::NavigateRight) {
+        view.length_index = (view.length_index + 1) % GAME_LENGTHS.len();
+    } else {
+        return;
+    }
+    view.rate_limit_selection.reset();
+    let seconds = GAME_LENGTHS[view.length_index];
+    text.sections[0].value = heading(&strings, seconds);
+    text.sections[1].value = table_text(&strings, &high_scores, &last_record, seconds);
+}
@@ -0,0 +1,349 @@
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// What a player can do, independent of the key or button that does it.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    Jump,
+    /// Hand control to the other character (solo only)
+    Switch,
+    /// Tell the companion to wait or to follow again, see `PlayMode::Companion`
+    Order,
+    /// Accept a menu entry or talk to someone
+    Confirm,
+    Pause,
+    NavigateUp,
+    NavigateDown,
+    NavigateLeft,
+    NavigateRight,
+}
+
+impl Action {
+    pub const ALL: [Action; 11] = [
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Jump,
+        Action::Switch,
+        Action::Order,
+        Action::Confirm,
+        Action::Pause,
+        Action::NavigateUp,
+        Action::NavigateDown,
+        Action::NavigateLeft,
+        Action::NavigateRight,
+    ];
+
+    /// String ID of the action's name on the controls screen
+    pub fn label(&self) -> &'static str {
+        match self {
+            Action::MoveLeft => "action.move_left",
+            Action::MoveRight => "action.move_right",
+            Action::Jump => "action.jump",
+            Action::Switch => "action.switch",
+            Action::Order => "action.order",
+            Action::Confirm => "action.confirm",
+            Action::Pause => "action.pause",
+            Action::NavigateUp => "action.navigate_up",
+            Action::NavigateDown => "action.navigate_down",
+            Action::NavigateLeft => "action.navigate_left",
+            Action::NavigateRight => "action.navigate_right",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Binding {
+    Key(KeyCode),
+    Button(GamepadButtonType),
+    /// Stick pushed past the threshold, `true` for the positive direction
+    Axis(GamepadAxisType, bool),
+}
+
+impl Binding {
+    pub fn label(&self) -> String {
+        match self {
+            Binding::Key(key) => format!("{:?}", key),
+            Binding::Button(button) => format!("{:?}", button),
+            Binding::Axis(axis, true) => format!("{:?}+", axis),
+            Binding::Axis(axis, false) => format!("{:?}-", axis),
+        }
+    }
+
+    /// Keys replace keys, buttons and sticks replace each other when rebinding.
+    pub fn is_gamepad(&self) -> bool {
+        !matches!(self, Binding::Key(_))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PlayerBindings {
+    /// Position of the player's gamepad among the connected ones
+    pub gamepad: usize,
+    pub actions: BTreeMap<Action, Vec<Binding>>,
+}
+
+/// Bindings of every player, kept in `Settings`.
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct InputBindings {
+    pub stick_threshold: f32,
+    /// Player 1 then player 2
+    pub players: Vec<PlayerBindings>,
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        use Binding::{Axis, Button, Key};
+        use GamepadAxisType::{LeftStickX, LeftStickY};
+        use GamepadButtonType::{DPadDown, DPadLeft, DPadRight, DPadUp, North, South, Start, West};
+
+        let player = |gamepad, left, right, up, down, jump, switch, order| PlayerBindings {
+            gamepad,
+            actions: BTreeMap::from([
+                (
+                    Action::MoveLeft,
+                    vec![Key(left), Button(DPadLeft), Axis(LeftStickX, false)],
+                ),
+                (
+                    Action::MoveRight,
+                    vec![Key(right), Button(DPadRight), Axis(LeftStickX, true)],
+                ),
+                (Action::Jump, vec![Key(jump), Button(South)]),
+                (Action::Switch, vec![Key(switch), Button(West)]),
+                (Action::Order, vec![Key(order), Button(North)]),
+                (Action::Confirm, vec![Key(jump), Button(South)]),
+                (Action::Pause, vec![Key(KeyCode::KeyP), Button(Start)]),
+                (
+                    Action::NavigateUp,
+                    vec![Key(up), Button(DPadUp), Axis(LeftStickY, true)],
+                ),
+                (
+                    Action::NavigateDown,
+                    vec![Key(down), Button(DPadDown), Axis(LeftStickY, false)],
+                ),
+                (
+                    Action::NavigateLeft,
+                    vec![Key(left), Button(DPadLeft), Axis(LeftStickX, false)],
+                ),
+                (
+                    Action::NavigateRight,
+                    vec![Key(right), Button(DPadRight), Axis(LeftStickX, true)],
+                ),
+            ]),
+        };
+
+        InputBindings {
+            stick_threshold: 0.10,
+            players: vec![
+                player(
+                    0,
+                    KeyCode::KeyA,
+                    KeyCode::KeyD,
+                    KeyCode::KeyW,
+                    KeyCode::KeyS,
+                    KeyCode::Space,
+                    KeyCode::ShiftLeft,
+                    KeyCode::KeyQ,
+                ),
+                player(
+                    1,
+                    KeyCode::ArrowLeft,
+                    KeyCode::ArrowRight,
+                    KeyCode::ArrowUp,
+                    KeyCode::ArrowDown,
+                    KeyCode::Enter,
+                    KeyCode::ShiftRight,
+                    KeyCode::ControlRight,
+                ),
+            ],
+        }
+    }
+}
+
+impl InputBindings {
+    /// Gives actions added since the bindings were saved their default bindings.
+    pub fn add_missing_actions(&mut self) {
+        let defaults = InputBindings::default();
+        for (player, default_player) in self.players.iter_mut().zip(defaults.players) {
+            for (action, action_bindings) in default_player.actions {
+                player.actions.entry(action).or_insert(action_bindings);
+            }
+        }
+    }
+}
+
+/// Which players' bindings drive a character.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PlayerControls {
+    /// Everyone's bindings, for one player switching between characters
+    Solo,
+    /// Player 1 in co-op
+    First,
+    /// Player 2 in co-op
+    Second,
+}
+
+impl PlayerControls {
+    fn players(&self) -> &'static [usize] {
+        match self {
+            PlayerControls::Solo => &[0, 1],
+            PlayerControls::First => &[0],
+            PlayerControls::Second => &[1],
+        }
+    }
+
+    pub fn pressed(&self, actions: &Actions, action: Action) -> bool {
+        self.players()
+            .iter()
+            .any(|player| actions.pressed(*player, action))
+    }
+
+    pub fn just_pressed(&self, actions: &Actions, action: Action) -> bool {
+        self.players()
+            .iter()
+            .any(|player| actions.just_pressed(*player, action))
+    }
+}
+
+/// Actions held and just pressed by each player this frame, read from `InputBindings`.
+#[derive(Resource, Default)]
+pub struct Actions {
+    players: Vec<ButtonInput<Action>>,
+}
+
+impl Actions {
+    pub fn pressed(&self, player: usize, action: Action) -> bool {
+        self.players
+            .get(player)
+            .is_some_and(|state| state.pressed(action))
+    }
+
+    pub fn just_pressed(&self, player: usize, action: Action) -> bool {
+        self.players
+            .get(player)
+            .is_some_and(|state| state.just_pressed(action))
+    }
+
+    /// Whether any player holds `action`, for menus everyone can drive.
+    pub fn any_pressed(&self, action: Action) -> bool {
+        self.players.iter().any(|state| state.pressed(action))
+    }
+
+    pub fn any_just_pressed(&self, action: Action) -> bool {
+        self.players.iter().any(|state| state.just_pressed(action))
+    }
+}
+
+/// Updates `Actions` from the raw keyboard and gamepad input, in `PreUpdate`.
+#[derive(SystemSet, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct ActionSet;
+
+pub struct ActionPlugin;
+
+impl Plugin for ActionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InputBindings>()
+            .init_resource::<Actions>()
+            .add_systems(
+                PreUpdate,
+                update_actions.in_set(ActionSet).after(InputSystem),
+            );
+    }
+}
+
+/// Returns the `index`th connected gamepad, ordered by id so players keep their pad.
+pub fn nth_gamepad(gamepads: &Gamepads, index: usize) -> Gamepad {
+    let mut connected = gamepads.iter().collect::<Vec<_>>();
+    connected.sort_by_key(|gamepad| gamepad.id);
+    match connected.get(index) {
+        Some(gp) => *gp,
+        None => Gamepad::new(index),
+    }
+}
+
+fn binding_active(
+    binding: &Binding,
+    gamepad: Gamepad,
+    stick_threshold: f32,
+    keyboard_input: &ButtonInput<KeyCode>,
+    button_inputs: &ButtonInput<GamepadButton>,
+    axes: &Axis<GamepadAxis>,
+) -> bool {
+    match binding {
+        Binding::Key(key) => keyboard_input.pressed(*key),
+        Binding::Button(button) => button_inputs.pressed(GamepadButton::new(gamepad, *button)),
+        Binding::Axis(axis, positive) => {
+            let value = axes.get(GamepadAxis::new(gamepad, *axis)).unwrap_or(0.0);
+            if *positive {
+                value > stick_threshold
+            } else {
+                value < -stick_threshold
+            }
+        }
+    }
+}
+
+fn update_actions(
+    bindings: Res<InputBindings>,
+    mut actions: ResMut<Actions>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    button_inputs: Res<ButtonInput<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+) {
+    actions
+        .players
+        .resize_with(bindings.players.len(), ButtonInput::default);
+    for (player_bindings, state) in bindings.players.iter().zip(actions.players.iter_mut()) {
+        state.clear();
+        let gamepad = nth_gamepad(&gamepads, player_bindings.gamepad);
+        for (action, action_bindings) in &player_bindings.actions {
+            let active = action_bindings.iter().any(|binding| {
+                binding_active(
+                    binding,
+                    gamepad,
+                    bindings.stick_threshold,
+                    &keyboard_input,
+                    &button_inputs,
+                    &axes,
+                )
+            });
+            if active {
+                state.press(*action);
+            } else {
+                state.release(*action);
+            }
+        }
+        // A key held while it was being bound must not count as a fresh press
+        if bindings.is_changed() {
+            state.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::headless::{headless_app, hold, player_x, press, run, start_game};
+
+    #[test]
+    fn rebound_key_moves_player() {
+        let mut app = headless_app();
+        start_game(&mut app);
+        app.world_mut().resource_mut::<InputBindings>().players[0]
+            .actions
+            .insert(Action::MoveLeft, vec![Binding::Key(KeyCode::KeyJ)]);
+        run(&mut app, 60);
+
+        let start = player_x(&mut app, 1);
+        hold(&mut app, KeyCode::KeyA, 20);
+        assert_eq!(player_x(&mut app, 1), start);
+
+        press(&mut app, KeyCode::KeyJ);
+        run(&mut app, 20);
+        assert!(player_x(&mut app, 1) < start);
+    }
+}
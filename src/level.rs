@@ -0,0 +1,319 @@
+use crate::dialog::DialogTree;
+use crate::game::{CloudSpawner, Dialog, Difficulty, Game, Platform, WaterCollectableSpawner};
+use crate::npc::{NpcRole, OfferingZone, OfferingZoneLabel};
+use crate::{launch_arg, AppState, InGame};
+use bevy::asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext};
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use serde::Deserialize;
+use std::ops::Range;
+use thiserror::Error;
+
+const DEFAULT_LEVEL: &str = "levels/sky.level.ron";
+
+/// A map layout loaded from a `.level.ron` file under `assets/levels`.
+#[derive(Asset, TypePath, Debug, Deserialize)]
+pub struct Level {
+    /// Points of the closed polyline that keeps players inside the world
+    pub bounds: Vec<Vec2>,
+    #[serde(default)]
+    pub platforms: Vec<LevelPlatform>,
+    #[serde(default)]
+    pub decorations: Vec<LevelDecoration>,
+    #[serde(default)]
+    pub cloud_spawners: Vec<LevelCloudSpawner>,
+    #[serde(default)]
+    pub collectable_spawners: Vec<LevelCollectableSpawner>,
+    #[serde(default)]
+    pub npcs: Vec<LevelNpc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LevelPlatform {
+    pub image: String,
+    pub position: Vec3,
+    /// Half extents of the cuboid collider
+    pub collider: Vec2,
+    pub height_adjustment: f32,
+    #[serde(default)]
+    pub flip_x: bool,
+    #[serde(default)]
+    pub flip_y: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LevelDecoration {
+    pub image: String,
+    pub position: Vec3,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LevelCloudSpawner {
+    pub image: String,
+    pub group: usize,
+    pub min_velocity: Vec2,
+    pub max_velocity: Vec2,
+    pub min_height: f32,
+    pub max_height: f32,
+    pub min_time: f32,
+    pub max_time: f32,
+    pub retry_time: f32,
+    pub probability: Range<i32>,
+    /// Half extents of the cuboid collider given to every spawned cloud
+    pub collider: Vec2,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LevelCollectableSpawner {
+    pub image: String,
+    pub min_height: f32,
+    pub max_height: f32,
+    pub min_time: f32,
+    pub max_time: f32,
+    pub retry_time: f32,
+    pub probability: Range<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LevelNpc {
+    pub image: String,
+    /// Bust shown in the dialog box
+    pub portrait: String,
+    pub position: Vec3,
+    /// Shown as it is unless there is a string ID by that name
+    pub title: String,
+    /// String ID
+    pub subtitle: String,
+    /// Path of the `.dialog.ron` file with what the NPC says
+    pub dialog: String,
+    /// What the NPC does with offered water
+    #[serde(default)]
+    pub role: NpcRole,
+    /// Where characters have to be for their water to count in offerings, anywhere when empty
+    #[serde(default)]
+    pub offering_zone: Option<LevelZone>,
+    /// Loaded along with the level
+    #[serde(skip)]
+    pub dialog_tree: Handle<DialogTree>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LevelZone {
+    /// Center of the zone relative to the NPC
+    pub offset: Vec2,
+    pub half_size: Vec2,
+}
+
+#[derive(Debug, Error)]
+pub enum LevelLoaderError {
+    #[error("Could not read level: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not parse level: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+}
+
+#[derive(Default)]
+pub struct LevelLoader;
+
+impl AssetLoader for LevelLoader {
+    type Asset = Level;
+    type Settings = ();
+    type Error = LevelLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Level, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let mut level = ron::de::from_bytes::<Level>(&bytes)?;
+        for npc in &mut level.npcs {
+            npc.dialog_tree = load_context.load(&npc.dialog);
+        }
+        Ok(level)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level.ron"]
+    }
+}
+
+/// The level played when a round starts.
+#[derive(Resource)]
+pub struct CurrentLevel {
+    pub handle: Handle<Level>,
+    spawned: bool,
+}
+
+pub struct LevelPlugin;
+
+impl Plugin for LevelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<Level>()
+            .init_asset_loader::<LevelLoader>()
+            .add_systems(Startup, load_level)
+            .add_systems(OnEnter(InGame), reset_level)
+            .add_systems(Update, spawn_level_system.run_if(in_state(AppState::Game)));
+    }
+}
+
+fn load_level(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let path = launch_arg("--level").unwrap_or_else(|| String::from(DEFAULT_LEVEL));
+    info!("Loading level {}", path);
+    commands.insert_resource(CurrentLevel {
+        handle: asset_server.load(path),
+        spawned: false,
+    });
+}
+
+fn reset_level(mut current_level: ResMut<CurrentLevel>) {
+    current_level.spawned = false;
+}
+
+fn spawn_level_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    levels: Res<Assets<Level>>,
+    mut current_level: ResMut<CurrentLevel>,
+    difficulty: Res<Difficulty>,
+) {
+    if current_level.spawned {
+        return;
+    }
+    let level = match levels.get(&current_level.handle) {
+        Some(l) => l,
+        None => return,
+    };
+    current_level.spawned = true;
+
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/PressStart2P-vaV7.ttf"),
+        font_size: 18.0,
+        ..default()
+    };
+
+    commands.spawn((
+        Game,
+        RigidBody::Fixed,
+        Collider::polyline(level.bounds.clone(), None),
+    ));
+
+    for platform in &level.platforms {
+        commands.spawn((
+            Game,
+            RigidBody::Fixed,
+            Collider::cuboid(platform.collider.x, platform.collider.y),
+            Platform {
+                height_adjustment: platform.height_adjustment,
+            },
+            CollisionGroups::new(Group::GROUP_10, Group::ALL),
+            ActiveHooks::FILTER_CONTACT_PAIRS,
+            SpriteBundle {
+                texture: asset_server.load(&platform.image),
+                transform: Transform::from_translation(platform.position),
+                sprite: Sprite {
+                    flip_x: platform.flip_x,
+                    flip_y: platform.flip_y,
+                    ..default()
+                },
+                ..default()
+            },
+        ));
+    }
+
+    for decoration in &level.decorations {
+        commands.spawn((
+            Game,
+            SpriteBundle {
+                texture: asset_server.load(&decoration.image),
+                transform: Transform::from_translation(decoration.position),
+                ..default()
+            },
+        ));
+    }
+
+    for spawner in &level.cloud_spawners {
+        commands.spawn((
+            Game,
+            CloudSpawner {
+                image: asset_server.load(&spawner.image),
+                group: spawner.group,
+                min_velocity: spawner.min_velocity * difficulty.cloud_speed(),
+                max_velocity: spawner.max_velocity * difficulty.cloud_speed(),
+                min_height: spawner.min_height,
+                max_height: spawner.max_height,
+                min_time: Timer::from_seconds(spawner.min_time, TimerMode::Once),
+                max_time: Timer::from_seconds(spawner.max_time, TimerMode::Once),
+                retry_time: Timer::from_seconds(spawner.retry_time, TimerMode::Repeating),
+                probability: difficulty.spawn_probability(&spawner.probability),
+                collider: Collider::cuboid(spawner.collider.x, spawner.collider.y),
+            },
+        ));
+    }
+
+    for spawner in &level.collectable_spawners {
+        commands.spawn((
+            Game,
+            WaterCollectableSpawner {
+                image: asset_server.load(&spawner.image),
+                min_height: spawner.min_height,
+                max_height: spawner.max_height,
+                min_time: Timer::from_seconds(spawner.min_time, TimerMode::Once),
+                max_time: Timer::from_seconds(spawner.max_time, TimerMode::Once),
+                retry_time: Timer::from_seconds(spawner.retry_time, TimerMode::Repeating),
+                probability: difficulty.spawn_probability(&spawner.probability),
+            },
+        ));
+    }
+
+    for npc in &level.npcs {
+        let mut entity = commands.spawn((
+            Game,
+            Dialog {
+                image: asset_server.load(&npc.portrait),
+                tree: npc.dialog_tree.clone(),
+                style: text_style.clone(),
+                title: npc.title.clone(),
+                subtitle: npc.subtitle.clone(),
+            },
+            npc.role.clone(),
+            SpriteBundle {
+                texture: asset_server.load(&npc.image),
+                transform: Transform::from_translation(npc.position),
+                ..default()
+            },
+        ));
+        if let Some(zone) = &npc.offering_zone {
+            entity.with_children(|parent| {
+                parent
+                    .spawn((
+                        OfferingZone::default(),
+                        Sensor,
+                        Collider::cuboid(zone.half_size.x, zone.half_size.y),
+                        SpatialBundle::from_transform(Transform::from_translation(
+                            zone.offset.extend(0.0),
+                        )),
+                    ))
+                    .with_children(|zone_parent| {
+                        zone_parent.spawn((
+                            OfferingZoneLabel,
+                            Text2dBundle {
+                                text: Text::from_section(
+                                    "",
+                                    TextStyle {
+                                        font_size: 12.0,
+                                        ..text_style.clone()
+                                    },
+                                ),
+                                // Along the top edge, in front of the NPC
+                                transform: Transform::from_xyz(0.0, zone.half_size.y - 12.0, 5.0),
+                                ..default()
+                            },
+                        ));
+                    });
+            });
+        }
+    }
+}
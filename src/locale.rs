@@ -0,0 +1,202 @@
+use ab_glyph::Font as _;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Display;
+
+/// Font used for all text.
+pub const FONT: &str = "fonts/PressStart2P-vaV7.ttf";
+/// Replaces `FONT` in text sections with characters it has no glyph for.
+pub const FALLBACK_FONT: &str = "fonts/DejaVuSansMono.ttf";
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    English,
+    Spanish,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::Spanish];
+
+    /// Name of the language in the language itself, the same in every table.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::Spanish => "Español",
+        }
+    }
+
+    pub fn next(&self) -> Language {
+        match self {
+            Language::English => Language::Spanish,
+            Language::Spanish => Language::English,
+        }
+    }
+
+    /// Where strings missing from this language's table are looked up next.
+    fn fallback(&self) -> Option<Language> {
+        match self {
+            Language::English => None,
+            Language::Spanish => Some(Language::English),
+        }
+    }
+
+    fn table(&self) -> &'static str {
+        match self {
+            Language::English => include_str!("../assets/locale/en.strings.ron"),
+            Language::Spanish => include_str!("../assets/locale/es.strings.ron"),
+        }
+    }
+}
+
+/// Player-facing text keyed by string ID, e.g. `menu.controls`, from `assets/locale`.
+///
+/// The tables are built into the executable so text is available from the first frame.
+#[derive(Resource)]
+pub struct Strings {
+    /// Picked in the menu and kept in `Settings`
+    pub language: Language,
+    tables: HashMap<Language, HashMap<String, String>>,
+}
+
+impl Default for Strings {
+    fn default() -> Self {
+        let tables = Language::ALL
+            .iter()
+            .map(|language| {
+                let table = ron::de::from_str::<HashMap<String, String>>(language.table())
+                    .unwrap_or_else(|e| {
+                        error!("Could not parse {:?} strings: {}", language, e);
+                        HashMap::new()
+                    });
+                (*language, table)
+            })
+            .collect();
+        Strings {
+            language: Language::default(),
+            tables,
+        }
+    }
+}
+
+impl Strings {
+    /// Text for `id` in the current language, falling back to other languages and then to
+    /// `id` itself, so names and numbers can be passed through as they are.
+    pub fn get<'a>(&'a self, id: &'a str) -> &'a str {
+        let mut language = Some(self.language);
+        while let Some(l) = language {
+            if let Some(text) = self.tables.get(&l).and_then(|t| t.get(id)) {
+                return text;
+            }
+            language = l.fallback();
+        }
+        id
+    }
+
+    /// Like `get`, with every `{name}` in the text replaced by its value.
+    pub fn format(&self, id: &str, args: &[(&str, &dyn Display)]) -> String {
+        args.iter()
+            .fold(self.get(id).to_string(), |text, (name, value)| {
+                text.replace(&format!("{{{}}}", name), &value.to_string())
+            })
+    }
+}
+
+#[derive(Resource)]
+struct FontHandles {
+    font: Handle<Font>,
+    fallback: Handle<Font>,
+}
+
+impl FromWorld for FontHandles {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        FontHandles {
+            font: asset_server.load(FONT),
+            fallback: asset_server.load(FALLBACK_FONT),
+        }
+    }
+}
+
+pub struct LocalePlugin;
+
+impl Plugin for LocalePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Strings>()
+            .init_resource::<FontHandles>()
+            .add_systems(PostUpdate, font_fallback_system);
+    }
+}
+
+/// Switches text sections between `FONT` and `FALLBACK_FONT` whenever their text changes.
+fn font_fallback_system(
+    handles: Res<FontHandles>,
+    fonts: Res<Assets<Font>>,
+    mut font_events: EventReader<AssetEvent<Font>>,
+    mut text_query: Query<&mut Text>,
+) {
+    let font = match fonts.get(&handles.font) {
+        Some(f) => f,
+        None => return,
+    };
+    // Text spawned before the font loaded is checked once it has
+    let loaded = font_events
+        .read()
+        .any(|e| e.is_loaded_with_dependencies(&handles.font));
+
+    for mut text in &mut text_query {
+        if !loaded && !text.is_changed() {
+            continue;
+        }
+        let changes = text
+            .sections
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| s.style.font == handles.font || s.style.font == handles.fallback)
+            .filter_map(|(index, s)| {
+                let missing = s
+                    .value
+                    .chars()
+                    .any(|c| !c.is_control() && font.font.glyph_id(c).0 == 0);
+                let wanted = if missing {
+                    &handles.fallback
+                } else {
+                    &handles.font
+                };
+                (s.style.font != *wanted).then(|| (index, wanted.clone()))
+            })
+            .collect::<Vec<_>>();
+        for (index, handle) in changes {
+            text.sections[index].style.font = handle;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::headless::{headless_app, run};
+    use crate::settings::SettingsBackend;
+    use crate::storage::MemoryStorage;
+
+    #[test]
+    fn picked_language_is_kept() {
+        let storage = MemoryStorage::default();
+        let mut app = headless_app();
+        app.insert_resource(SettingsBackend(Box::new(storage.clone())));
+        run(&mut app, 2);
+        app.world_mut().resource_mut::<Strings>().language = Language::Spanish;
+        run(&mut app, 2);
+
+        let mut app = headless_app();
+        app.insert_resource(SettingsBackend(Box::new(storage)));
+        run(&mut app, 1);
+        let strings = app.world().resource::<Strings>();
+        assert_eq!(strings.language, Language::Spanish);
+        assert_eq!(strings.get("dialog.yes"), "Sí");
+        assert_eq!(strings.get("menu.controls"), "Controles");
+        // Names are not in any table and are shown as they are
+        assert_eq!(strings.get("Tlaloc"), "Tlaloc");
+    }
+}
@@ -3,13 +3,33 @@ use bevy::prelude::*;
 use bevy_prng::ChaCha8Rng;
 use bevy_rand::prelude::EntropyPlugin;
 use bevy_rapier2d::prelude::*;
+mod achievements;
 mod camera;
 mod camera_tracking;
+mod companion;
+mod controls;
+mod dialog;
 mod game;
 mod gameover;
+#[cfg(test)]
+mod headless;
+mod highscores;
+mod input;
+mod level;
+mod locale;
 mod menu;
+mod movement;
+mod npc;
+mod pause;
+mod replay;
+mod scoring;
+mod seed;
+mod settings;
 mod setup;
 mod splash;
+mod stats;
+mod storage;
+mod telemetry;
 
 const PLAYER_MOVEMENT_SPEED: f32 = 250.;
 pub const MAXIMUM_DOWNWARD_VELOCITY: f32 = 400.0;
@@ -23,38 +43,104 @@ enum AppState {
     Menu,
     Game,
     GameOver,
+    Controls,
+    HighScores,
+    Achievements,
 }
 
+/// Active while a round is in progress, paused or not.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+struct InGame;
+
+impl ComputedStates for InGame {
+    type SourceStates = AppState;
+
+    fn compute(sources: AppState) -> Option<Self> {
+        match sources {
+            AppState::Game | AppState::Pause => Some(InGame),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Music;
+
 fn despawn_screen<T: Component>(to_despawn: Query<Entity, With<T>>, mut commands: Commands) {
     for entity in &to_despawn {
         commands.entity(entity).despawn_recursive();
     }
 }
 
+/// Returns the value following `name` on the command line, e.g. `--level levels/sky.level.ron`.
+pub fn launch_arg(name: &str) -> Option<String> {
+    let mut args = std::env::args().skip_while(|arg| arg != name);
+    args.next()?;
+    args.next()
+}
+
 fn music(asset_server: Res<AssetServer>, mut commands: Commands) {
-    commands.spawn(AudioBundle {
-        source: asset_server.load("water.mp3"),
-        settings: PlaybackSettings {
-            mode: PlaybackMode::Loop,
-            ..default()
+    commands.spawn((
+        Music,
+        AudioBundle {
+            source: asset_server.load("water.mp3"),
+            settings: PlaybackSettings {
+                mode: PlaybackMode::Loop,
+                ..default()
+            },
         },
-        ..default()
-    });
+    ));
+}
+
+/// Physics, states and every screen and feature of the game, without the window, rendering or
+/// audio output that `setup::WindowSetup` brings. `headless::headless_app` runs the same.
+struct GamePlugins;
+
+impl Plugin for GamePlugins {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(RapierPhysicsPlugin::<game::OneWayPlatforms>::pixels_per_meter(100.0))
+            .add_plugins(EntropyPlugin::<ChaCha8Rng>::default())
+            .init_state::<AppState>()
+            .add_computed_state::<InGame>()
+            .add_plugins((
+                splash::SplashPlugin,
+                menu::MenuPlugin,
+                gameover::GameOverPlugin,
+                game::PlatformPlugin,
+                level::LevelPlugin,
+                locale::LocalePlugin,
+                dialog::DialogPlugin,
+                seed::SeedPlugin,
+                replay::ReplayPlugin,
+                pause::PausePlugin,
+                input::ActionPlugin,
+                controls::ControlsPlugin,
+                settings::SettingsPlugin,
+                highscores::HighScoresPlugin,
+            ))
+            .add_plugins((
+                scoring::ScoringPlugin,
+                stats::StatsPlugin,
+                telemetry::TelemetryPlugin,
+                achievements::AchievementsPlugin,
+                companion::CompanionPlugin,
+            ));
+    }
 }
 
 fn main() {
+    if let Some(paths) = telemetry::summary_paths() {
+        match telemetry::summarize(&paths) {
+            Ok(summary) => println!("{}", summary),
+            Err(e) => eprintln!("Could not read telemetry: {}", e),
+        }
+        return;
+    }
+
     App::new()
         .add_plugins((setup::WindowSetup, camera::CameraPlugin))
-        .add_plugins(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.0))
-        .add_plugins(EntropyPlugin::<ChaCha8Rng>::default())
         // .add_plugins(RapierDebugRenderPlugin::default())
-        .init_state::<AppState>()
+        .add_plugins(GamePlugins)
         .add_systems(Startup, music)
-        .add_plugins((
-            splash::SplashPlugin,
-            menu::MenuPlugin,
-            gameover::GameOverPlugin,
-            game::PlatformPlugin,
-        ))
         .run();
 }
@@ -1,5 +1,11 @@
 use crate::despawn_screen;
+use crate::game::{Difficulty, PlayMode};
+use crate::highscores::GAME_LENGTHS;
+use crate::input::{Action, Actions};
+use crate::locale::Strings;
+use crate::seed::RunSeed;
 use crate::AppState;
+use bevy::ecs::system::SystemParam;
 use bevy::scene::ron::de;
 use bevy::{prelude::*, render::view::RenderLayers};
 
@@ -15,12 +21,34 @@ pub struct MenuScrollControl {
 #[derive(Resource, Deref, DerefMut)]
 pub struct TotalSeconds(pub f32);
 
+/// The entries listed under the game lengths, in selection order.
+#[derive(Clone, Copy)]
+enum MenuEntry {
+    Difficulty,
+    Seed,
+    Players,
+    Language,
+    Controls,
+    HighScores,
+    Achievements,
+}
+
+const MENU_ENTRIES: [MenuEntry; 7] = [
+    MenuEntry::Difficulty,
+    MenuEntry::Seed,
+    MenuEntry::Players,
+    MenuEntry::Language,
+    MenuEntry::Controls,
+    MenuEntry::HighScores,
+    MenuEntry::Achievements,
+];
+
 pub struct MenuPlugin;
 
 impl Plugin for MenuPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(AppState::Menu), menu_setup)
-            .insert_resource(TotalSeconds(180.0))
+            .insert_resource(TotalSeconds(GAME_LENGTHS[0] as f32))
             .add_systems(
                 Update,
                 menu_selection_system.run_if(in_state(AppState::Menu)),
@@ -32,9 +60,31 @@ impl Plugin for MenuPlugin {
 #[derive(Component)]
 struct MenuCamera;
 
-fn menu_setup(mut commands: Commands, asset_server: Res<AssetServer>, mut bg: ResMut<ClearColor>) {
+/// The settings the menu shows next to their entries and changes when they are picked.
+#[derive(SystemParam)]
+struct MenuOptions<'w> {
+    run_seed: ResMut<'w, RunSeed>,
+    play_mode: ResMut<'w, PlayMode>,
+    difficulty: ResMut<'w, Difficulty>,
+    strings: ResMut<'w, Strings>,
+}
+
+fn menu_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut bg: ResMut<ClearColor>,
+    options: MenuOptions,
+    total_seconds: Res<TotalSeconds>,
+) {
     bg.0 = Color::srgb(0.7, 0.7, 0.7);
 
+    // Start on the game length picked last time
+    let selection_index = GAME_LENGTHS
+        .iter()
+        .position(|&seconds| seconds == total_seconds.0 as u32)
+        .unwrap_or(0)
+        + 1;
+
     commands.spawn((
         MenuScreen,
         MenuCamera,
@@ -68,56 +118,28 @@ fn menu_setup(mut commands: Commands, asset_server: Res<AssetServer>, mut bg: Re
         },
     ));
 
+    let style = TextStyle {
+        font: asset_server.load("fonts/PressStart2P-vaV7.ttf"),
+        font_size: 18.0,
+        color: Color::BLACK,
+    };
     commands.spawn((
         MenuScreen,
         MenuScrollControl {
             rate_limit_selection: Timer::from_seconds(0.2, TimerMode::Once),
-            selection_index: 1,
+            selection_index,
         },
         RenderLayers::layer(2),
-        TextBundle::from_sections(vec![
-            TextSection {
-                value: String::from("Game length:\n\n"),
-                style: TextStyle {
-                    font: asset_server.load("fonts/PressStart2P-vaV7.ttf"),
-                    font_size: 32.0,
-                    color: Color::BLACK,
-                    ..default()
-                },
-            },
-            TextSection {
-                value: String::from("▶  180 seconds (3 minutes)\n\n"),
-                style: TextStyle {
-                    font: asset_server.load("fonts/PressStart2P-vaV7.ttf"),
-                    font_size: 32.0,
-                    color: Color::BLACK,
-                    ..default()
-                },
-            },
-            TextSection {
-                value: String::from("  240 seconds (4 minutes)\n\n"),
-                style: TextStyle {
-                    font: asset_server.load("fonts/PressStart2P-vaV7.ttf"),
-                    font_size: 32.0,
-                    color: Color::BLACK,
-                    ..default()
-                },
-            },
-            TextSection {
-                value: String::from("  300 seconds (5 minutes)"),
-                style: TextStyle {
-                    font: asset_server.load("fonts/PressStart2P-vaV7.ttf"),
-                    font_size: 32.0,
-                    color: Color::BLACK,
-                    ..default()
-                },
-            },
-        ])
+        TextBundle::from_sections(menu_sections(
+            &menu_items(&options),
+            selection_index,
+            &style,
+        ))
         .with_text_justify(JustifyText::Left)
         .with_style(Style {
             position_type: PositionType::Absolute,
             // align_items: AlignItems::Center,
-            top: Val::Percent(20.0),
+            top: Val::Percent(16.0),
             left: Val::Percent(20.0),
 
             ..default()
@@ -130,7 +152,7 @@ fn menu_setup(mut commands: Commands, asset_server: Res<AssetServer>, mut bg: Re
         SpriteBundle {
             texture: asset_server.load("synopsys.png"),
             transform: Transform {
-                translation: Vec3::new(0.0, -120.0, 10.0),
+                translation: Vec3::new(0.0, -180.0, 10.0),
                 ..default()
             },
             ..default()
@@ -143,7 +165,7 @@ fn menu_setup(mut commands: Commands, asset_server: Res<AssetServer>, mut bg: Re
         SpriteBundle {
             texture: asset_server.load("gamecontroller.png"),
             transform: Transform {
-                translation: Vec3::new(400.0, -270.0, 10.0),
+                translation: Vec3::new(400.0, -300.0, 10.0),
                 ..default()
             },
             ..default()
@@ -156,7 +178,7 @@ fn menu_setup(mut commands: Commands, asset_server: Res<AssetServer>, mut bg: Re
         SpriteBundle {
             texture: asset_server.load("keyboard.png"),
             transform: Transform {
-                translation: Vec3::new(-400.0, -270.0, 10.0),
+                translation: Vec3::new(-400.0, -300.0, 10.0),
                 ..default()
             },
             ..default()
@@ -166,30 +188,67 @@ fn menu_setup(mut commands: Commands, asset_server: Res<AssetServer>, mut bg: Re
     // corner indicator of selected player
 }
 
+/// The heading, then one line per menu entry in selection order.
+fn menu_items(options: &MenuOptions) -> Vec<String> {
+    let strings = &options.strings;
+    let mut items = vec![strings.get("menu.game_length").to_string()];
+    items.extend(GAME_LENGTHS.iter().map(|seconds| {
+        strings.format(
+            "menu.length",
+            &[("seconds", seconds), ("minutes", &(seconds / 60))],
+        )
+    }));
+    items.extend(MENU_ENTRIES.iter().map(|entry| match entry {
+        MenuEntry::Difficulty => strings.format(
+            "menu.difficulty",
+            &[("difficulty", &strings.get(options.difficulty.label()))],
+        ),
+        MenuEntry::Seed => strings.format(
+            "menu.seed",
+            &[("mode", &strings.get(&options.run_seed.mode.label()))],
+        ),
+        MenuEntry::Players => strings.format(
+            "menu.players",
+            &[("count", &strings.get(options.play_mode.label()))],
+        ),
+        MenuEntry::Language => {
+            strings.format("menu.language", &[("language", &strings.language.label())])
+        }
+        MenuEntry::Controls => strings.get("menu.controls").to_string(),
+        MenuEntry::HighScores => strings.get("menu.high_scores").to_string(),
+        MenuEntry::Achievements => strings.get("menu.achievements").to_string(),
+    }));
+    items
+}
+
+fn menu_sections(items: &[String], selection_index: usize, style: &TextStyle) -> Vec<TextSection> {
+    let last = items.len() - 1;
+    items
+        .iter()
+        .enumerate()
+        .map(|(index, item)| {
+            let marker = if index == selection_index { "▶" } else { "" };
+            let indent = if index == 0 { "" } else { "  " };
+            let end = if index == last { "" } else { "\n\n" };
+            TextSection {
+                value: format!("{}{}{}{}", marker, indent, item, end),
+                style: style.clone(),
+            }
+        })
+        .collect()
+}
+
 fn menu_selection_system(
     time: Res<Time>,
     mut app_state: ResMut<NextState<AppState>>,
     mut total_seconds: ResMut<TotalSeconds>,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    gamepads: Res<Gamepads>,
-    button_inputs: Res<ButtonInput<GamepadButton>>,
-    axes: Res<Axis<GamepadAxis>>,
+    mut options: MenuOptions,
+    actions: Res<Actions>,
     mut time_selection_query: Query<(&mut Text, &mut MenuScrollControl)>,
 ) {
-    let gamepad = match gamepads.iter().next() {
-        Some(gp) => gp,
-        None => Gamepad::new(0),
-    };
-    let left_stick_y = match axes.get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickY)) {
-        Some(y) => y,
-        None => 0.0,
-    };
-
-    let up_key_pressed = left_stick_y > 0.10 || keyboard_input.pressed(KeyCode::ArrowUp);
-    let down_key_pressed = left_stick_y < -0.10 || keyboard_input.pressed(KeyCode::ArrowDown);
-    let jump_key_just_pressed = button_inputs
-        .just_pressed(GamepadButton::new(gamepad, GamepadButtonType::South))
-        || keyboard_input.just_pressed(KeyCode::Space);
+    let up_key_pressed = actions.any_pressed(Action::NavigateUp);
+    let down_key_pressed = actions.any_pressed(Action::NavigateDown);
+    let jump_key_just_pressed = actions.any_just_pressed(Action::Confirm);
 
     let (mut text_sections, mut menu_select_control) = time_selection_query.single_mut();
 
@@ -198,20 +257,16 @@ fn menu_selection_system(
     if menu_select_control.rate_limit_selection.finished()
         || menu_select_control.rate_limit_selection.just_finished()
     {
+        let last = GAME_LENGTHS.len() + MENU_ENTRIES.len();
         if up_key_pressed {
-            menu_select_control.selection_index -= 1;
+            menu_select_control.selection_index =
+                menu_select_control.selection_index.saturating_sub(1);
             menu_select_control.rate_limit_selection.reset();
         } else if down_key_pressed {
             menu_select_control.selection_index += 1;
             menu_select_control.rate_limit_selection.reset();
         }
-
-        if menu_select_control.selection_index < 1 {
-            menu_select_control.selection_index = 1
-        }
-        if menu_select_control.selection_index > 3 {
-            menu_select_control.selection_index = 3
-        }
+        menu_select_control.selection_index = menu_select_control.selection_index.clamp(1, last);
 
         for (usize, mut text_section) in text_sections.sections.iter_mut().enumerate() {
             if usize == 0 {
@@ -227,15 +282,32 @@ fn menu_selection_system(
         }
     }
 
-    if jump_key_just_pressed {
-        if menu_select_control.selection_index == 1 {
-            total_seconds.0 = 180.0;
-        } else if menu_select_control.selection_index == 2 {
-            total_seconds.0 = 240.0;
-        } else {
-            total_seconds.0 = 300.0;
-        }
+    if !jump_key_just_pressed {
+        return;
+    }
 
+    let index = menu_select_control.selection_index;
+    if index <= GAME_LENGTHS.len() {
+        total_seconds.0 = GAME_LENGTHS[index - 1] as f32;
         app_state.set(AppState::Game);
+        return;
+    }
+
+    match MENU_ENTRIES[index - GAME_LENGTHS.len() - 1] {
+        MenuEntry::Controls => app_state.set(AppState::Controls),
+        MenuEntry::HighScores => app_state.set(AppState::HighScores),
+        MenuEntry::Achievements => app_state.set(AppState::Achievements),
+        entry => {
+            match entry {
+                MenuEntry::Difficulty => *options.difficulty = options.difficulty.next(),
+                MenuEntry::Seed => {
+                    options.run_seed.mode = options.run_seed.mode.next(options.run_seed.fixed)
+                }
+                MenuEntry::Players => *options.play_mode = options.play_mode.next(),
+                _ => options.strings.language = options.strings.language.next(),
+            }
+            let style = text_sections.sections[0].style.clone();
+            text_sections.sections = menu_sections(&menu_items(&options), index, &style);
+        }
     }
 }
@@ -0,0 +1,163 @@
+use crate::{CHARACTER_DOWNWARD_VELOCITY_PER_FRAME, MAXIMUM_DOWNWARD_VELOCITY};
+use bevy::prelude::*;
+
+/// Upward speed at the start of a jump, in pixels per second.
+const JUMP_SPEED: f32 = 600.0;
+/// Length of a jump carrying no water.
+const LIGHT_JUMP_SECONDS: f32 = 1.4;
+
+/// Vertical state of one character, advanced by `MovementModel::step`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Motion {
+    /// Pixels per second, positive is up
+    pub velocity: f32,
+    /// Still going up through a jump
+    pub rising: bool,
+    /// Seconds into the current jump
+    pub jump_time: f32,
+    /// How long the current jump lasts, see `MovementModel::jump_seconds`
+    pub jump_seconds: f32,
+}
+
+/// How characters jump and fall. Jumps slow down linearly, falls speed up along a cosine.
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct MovementModel {
+    /// Upward speed at the start of a jump
+    pub jump_speed: f32,
+    /// Pixels per second gained each second when starting to fall
+    pub fall_acceleration: f32,
+    /// Falls never get faster than this
+    pub max_fall_speed: f32,
+    /// Multiplies how much carried water shortens jumps, see `Difficulty::weight_penalty`
+    pub weight_penalty: f32,
+}
+
+impl Default for MovementModel {
+    fn default() -> Self {
+        MovementModel {
+            jump_speed: JUMP_SPEED,
+            fall_acceleration: -CHARACTER_DOWNWARD_VELOCITY_PER_FRAME,
+            max_fall_speed: MAXIMUM_DOWNWARD_VELOCITY,
+            weight_penalty: 1.0,
+        }
+    }
+}
+
+impl MovementModel {
+    /// Seconds a jump lasts while carrying `water` drops. Heavy loads keep a short hop.
+    pub fn jump_seconds(&self, water: u32) -> f32 {
+        let seconds = if water > 15 {
+            0.4 - 0.015 * (water - 14) as f32
+        } else if water > 8 {
+            0.8 - 0.05 * (water - 7) as f32
+        } else {
+            LIGHT_JUMP_SECONDS - 0.1 * water as f32
+        };
+        let seconds = LIGHT_JUMP_SECONDS - (LIGHT_JUMP_SECONDS - seconds) * self.weight_penalty;
+        if seconds < 0.3 {
+            0.2
+        } else {
+            seconds
+        }
+    }
+
+    /// Height gained by a full jump while carrying `water` drops.
+    pub fn jump_height(&self, water: u32) -> f32 {
+        self.jump_speed * self.jump_seconds(water) / 2.0
+    }
+
+    /// Starts a jump from wherever the character is.
+    pub fn jump(&self, motion: &mut Motion, water: u32) {
+        *motion = Motion {
+            velocity: self.jump_speed,
+            rising: true,
+            jump_time: 0.0,
+            jump_seconds: self.jump_seconds(water),
+        };
+    }
+
+    /// Advances `motion` by `delta` seconds. Letting go of jump makes the rest of it go twice as
+    /// fast.
+    pub fn step(&self, motion: &mut Motion, jump_held: bool, delta: f32) {
+        if motion.rising {
+            let speed = if jump_held { 1.0 } else { 2.0 };
+            motion.jump_time = (motion.jump_time + speed * delta).min(motion.jump_seconds);
+            let jump_percent = if motion.jump_seconds > 0.0 {
+                motion.jump_time / motion.jump_seconds
+            } else {
+                1.0
+            };
+            motion.velocity = self.jump_speed * (1.0 - jump_percent);
+            motion.rising = jump_percent < 1.0;
+        } else if motion.velocity > 0.0 {
+            // The top of the jump
+            motion.velocity = 0.0;
+        } else {
+            let d = -motion.velocity / self.max_fall_speed;
+            motion.velocity = (motion.velocity - self.fall_acceleration * delta * d.cos())
+                .max(-self.max_fall_speed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DELTA: f32 = 1.0 / 60.0;
+
+    /// Height gained from the start of a jump to its top.
+    fn apex(model: &MovementModel, water: u32, jump_held: bool) -> f32 {
+        let mut motion = Motion::default();
+        model.jump(&mut motion, water);
+        let mut height = 0.0;
+        loop {
+            model.step(&mut motion, jump_held, DELTA);
+            if motion.velocity <= 0.0 {
+                return height;
+            }
+            height += motion.velocity * DELTA;
+        }
+    }
+
+    #[test]
+    fn jump_peaks_at_half_its_speed_times_its_length() {
+        let model = MovementModel::default();
+        let expected = JUMP_SPEED * LIGHT_JUMP_SECONDS / 2.0;
+        assert_eq!(model.jump_height(0), expected);
+        assert!((apex(&model, 0, true) - expected).abs() < JUMP_SPEED * DELTA);
+        // Letting go right away halves the jump
+        assert!((apex(&model, 0, false) - expected / 2.0).abs() < JUMP_SPEED * DELTA);
+    }
+
+    #[test]
+    fn fall_speed_is_capped() {
+        let model = MovementModel::default();
+        let mut motion = Motion::default();
+        for _ in 0..600 {
+            model.step(&mut motion, false, DELTA);
+            assert!(motion.velocity >= -MAXIMUM_DOWNWARD_VELOCITY);
+        }
+        assert_eq!(motion.velocity, -MAXIMUM_DOWNWARD_VELOCITY);
+    }
+
+    #[test]
+    fn water_shortens_jumps() {
+        let model = MovementModel::default();
+        assert_eq!(model.jump_seconds(0), LIGHT_JUMP_SECONDS);
+        assert!((model.jump_seconds(4) - 1.0).abs() < 1e-5);
+        assert!((model.jump_seconds(15) - 0.4).abs() < 1e-5);
+        for water in 1..40 {
+            assert!(model.jump_seconds(water) < model.jump_seconds(0));
+            assert!(model.jump_seconds(water) >= 0.2);
+        }
+        assert_eq!(model.jump_seconds(40), 0.2);
+
+        let hard = MovementModel {
+            weight_penalty: 1.3,
+            ..default()
+        };
+        assert!(hard.jump_seconds(4) < model.jump_seconds(4));
+        assert!(apex(&hard, 4, true) < apex(&model, 4, true));
+    }
+}
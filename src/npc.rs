@@ -0,0 +1,153 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// What an NPC does with the water offered to it through the dialog's `Offer` effect.
+///
+/// Set per NPC in the level file, e.g. `role: Shrine(multiplier: 2)`.
+#[derive(Component, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum NpcRole {
+    /// Turns the offered water into score
+    Shrine {
+        #[serde(default = "default_multiplier")]
+        multiplier: u32,
+    },
+    /// Takes the water away so the characters get lighter
+    Cleanser,
+    /// Buys the offered water, one point for every `price` drops
+    Merchant {
+        #[serde(default = "default_price")]
+        price: u32,
+    },
+    /// Only takes an offering of at least `wanted` drops, and pays a fixed `reward` for it
+    QuestGiver { wanted: u32, reward: u32 },
+    /// Only talks
+    #[default]
+    Storyteller,
+}
+
+fn default_multiplier() -> u32 {
+    1
+}
+
+fn default_price() -> u32 {
+    1
+}
+
+impl NpcRole {
+    /// Score for `water` drops offered by the characters in range.
+    pub fn offering_score(&self, water: u32) -> u32 {
+        match self {
+            NpcRole::Shrine { multiplier } => water * multiplier,
+            NpcRole::Merchant { price } => water / (*price).max(1),
+            NpcRole::QuestGiver { wanted, reward } if water >= *wanted => *reward,
+            _ => 0,
+        }
+    }
+
+    /// Whether an offering of `water` drops empties both characters' water.
+    pub fn takes_water(&self, water: u32) -> bool {
+        match self {
+            NpcRole::Storyteller => false,
+            NpcRole::QuestGiver { wanted, .. } => water >= *wanted,
+            _ => true,
+        }
+    }
+}
+
+/// Sensor region spawned as a child of an NPC, only characters inside it take part in the
+/// NPC's offerings. NPCs without one accept water from anywhere.
+#[derive(Component, Default)]
+pub struct OfferingZone {
+    /// Numbers of the players inside, updated every frame
+    pub players: Vec<usize>,
+}
+
+/// Text over an `OfferingZone`, its child, naming the players inside.
+#[derive(Component)]
+pub struct OfferingZoneLabel;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{TotalScore, WaterCollection};
+    use crate::headless::{
+        headless_app, offer_at_chalchiuhtlicue, reach_tlaloc_choice, start_game, tap,
+    };
+    use crate::stats::RoundStats;
+
+    #[test]
+    fn only_shrines_score_offerings() {
+        assert_eq!(NpcRole::Shrine { multiplier: 1 }.offering_score(5), 5);
+        assert_eq!(NpcRole::Shrine { multiplier: 2 }.offering_score(5), 10);
+        assert_eq!(NpcRole::Cleanser.offering_score(5), 0);
+        assert_eq!(NpcRole::Storyteller.offering_score(5), 0);
+    }
+
+    #[test]
+    fn merchants_pay_for_every_full_price() {
+        assert_eq!(NpcRole::Merchant { price: 3 }.offering_score(8), 2);
+        assert_eq!(NpcRole::Merchant { price: 3 }.offering_score(2), 0);
+        // The change is taken as well
+        assert!(NpcRole::Merchant { price: 3 }.takes_water(2));
+    }
+
+    #[test]
+    fn quest_givers_only_take_what_they_asked_for() {
+        let role = NpcRole::QuestGiver {
+            wanted: 10,
+            reward: 25,
+        };
+        assert_eq!(role.offering_score(12), 25);
+        assert!(role.takes_water(12));
+        assert_eq!(role.offering_score(9), 0);
+        assert!(!role.takes_water(9));
+    }
+
+    #[test]
+    fn storytellers_leave_the_water() {
+        assert!(NpcRole::Shrine { multiplier: 1 }.takes_water(5));
+        assert!(NpcRole::Cleanser.takes_water(5));
+        assert!(!NpcRole::Storyteller.takes_water(5));
+    }
+
+    #[test]
+    fn only_water_inside_the_offering_zone_counts() {
+        let mut app = headless_app();
+        start_game(&mut app);
+
+        app.world_mut()
+            .resource_mut::<WaterCollection>()
+            .total_player2 = 3;
+        reach_tlaloc_choice(&mut app, 5);
+
+        let mut labels = app
+            .world_mut()
+            .query_filtered::<&Text, With<OfferingZoneLabel>>();
+        let label = labels.single(app.world()).sections[0].value.clone();
+        assert_eq!(label, "Offering: Nena");
+        tap(&mut app, KeyCode::ArrowRight);
+        tap(&mut app, KeyCode::Space);
+        // Nata stayed on the ground, the shrine still takes her drops
+        assert_eq!(app.world().resource::<TotalScore>().0, 5);
+        assert_eq!(app.world().resource::<WaterCollection>().total_player2, 0);
+        let stats = app.world().resource::<RoundStats>();
+        assert_eq!(stats.players[0].offerings, 1);
+        assert_eq!(stats.players[0].biggest_offering, 5);
+        assert_eq!(stats.players[1].offerings, 0);
+        assert_eq!(stats.players[1].lost, 3);
+    }
+
+    #[test]
+    fn river_goddess_takes_water_without_scoring() {
+        let mut app = headless_app();
+        start_game(&mut app);
+
+        app.world_mut()
+            .resource_mut::<WaterCollection>()
+            .total_player1 = 3;
+        offer_at_chalchiuhtlicue(&mut app);
+
+        assert_eq!(app.world().resource::<TotalScore>().0, 0);
+        assert_eq!(app.world().resource::<WaterCollection>().total_player1, 0);
+    }
+}
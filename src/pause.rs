@@ -0,0 +1,290 @@
+use crate::input::{Action, Actions};
+use crate::locale::Strings;
+use crate::settings::Settings;
+use crate::{despawn_screen, AppState, InGame};
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+const PAUSE_ITEMS: [&str; 4] = [
+    "pause.resume",
+    "pause.restart",
+    "pause.options",
+    "pause.quit",
+];
+
+#[derive(Component)]
+pub struct OnPauseScreen;
+
+#[derive(Component)]
+struct PauseMenu {
+    rate_limit_selection: Timer,
+    selection_index: usize,
+    options_open: bool,
+}
+
+/// Set when "Restart" is chosen so the round is torn down and set up again on resume.
+#[derive(Resource)]
+struct RestartRound;
+
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::Pause), (pause_setup, freeze_physics))
+            .add_systems(
+                Update,
+                pause_selection_system.run_if(in_state(AppState::Pause)),
+            )
+            .add_systems(
+                OnExit(AppState::Pause),
+                (despawn_screen::<OnPauseScreen>, unfreeze_physics),
+            )
+            .add_systems(
+                OnTransition {
+                    exited: AppState::Pause,
+                    entered: AppState::Game,
+                },
+                restart_round.run_if(resource_exists::<RestartRound>),
+            );
+    }
+}
+
+fn freeze_physics(mut rapier_configuration: ResMut<RapierConfiguration>) {
+    rapier_configuration.physics_pipeline_active = false;
+}
+
+fn unfreeze_physics(mut rapier_configuration: ResMut<RapierConfiguration>) {
+    rapier_configuration.physics_pipeline_active = true;
+}
+
+/// Runs the round teardown and setup schedules back to back without leaving the game.
+fn restart_round(world: &mut World) {
+    world.remove_resource::<RestartRound>();
+    world.run_schedule(OnExit(InGame));
+    world.run_schedule(OnEnter(InGame));
+}
+
+fn pause_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    settings: Res<Settings>,
+    strings: Res<Strings>,
+) {
+    let style = TextStyle {
+        font: asset_server.load("fonts/PressStart2P-vaV7.ttf"),
+        font_size: 32.0,
+        color: Color::WHITE,
+    };
+
+    commands
+        .spawn((
+            OnPauseScreen,
+            NodeBundle {
+                background_color: BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.7)),
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                z_index: ZIndex::Global(10),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                PauseMenu {
+                    rate_limit_selection: Timer::from_seconds(0.2, TimerMode::Once),
+                    selection_index: 0,
+                    options_open: false,
+                },
+                TextBundle::from_sections(pause_sections(
+                    &strings,
+                    &pause_items(false, &settings, &strings),
+                    0,
+                    &style,
+                ))
+                .with_text_justify(JustifyText::Left),
+            ));
+        });
+}
+
+fn pause_sections(
+    strings: &Strings,
+    items: &[String],
+    selection_index: usize,
+    style: &TextStyle,
+) -> Vec<TextSection> {
+    let mut sections = vec![TextSection {
+        value: format!("{}\n\n", strings.get("pause.title")),
+        style: style.clone(),
+    }];
+    sections.extend(items.iter().enumerate().map(|(i, item)| TextSection {
+        value: if i == selection_index {
+            format!("▶ {}\n\n", item)
+        } else {
+            format!("  {}\n\n", item)
+        },
+        style: style.clone(),
+    }));
+    sections
+}
+
+fn pause_items(options_open: bool, settings: &Settings, strings: &Strings) -> Vec<String> {
+    if options_open {
+        vec![
+            strings.format(
+                "pause.music_volume",
+                &[("percent", &(settings.music_volume * 100.0).round())],
+            ),
+            strings.format(
+                "pause.sound_volume",
+                &[("percent", &(settings.sfx_volume * 100.0).round())],
+            ),
+            strings.format(
+                "pause.window",
+                &[("mode", &strings.get(settings.display.label()))],
+            ),
+            strings.get("common.back").to_string(),
+        ]
+    } else {
+        PAUSE_ITEMS.map(|id| strings.get(id).to_string()).to_vec()
+    }
+}
+
+fn step_volume(volume: f32, step: f32) -> f32 {
+    ((volume + step) * 10.0).round().clamp(0.0, 10.0) / 10.0
+}
+
+fn pause_selection_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut app_state: ResMut<NextState<AppState>>,
+    mut settings: ResMut<Settings>,
+    strings: Res<Strings>,
+    actions: Res<Actions>,
+    mut pause_menu_query: Query<(&mut Text, &mut PauseMenu)>,
+) {
+    let up_key_pressed = actions.any_pressed(Action::NavigateUp);
+    let down_key_pressed = actions.any_pressed(Action::NavigateDown);
+    let left_key_pressed = actions.any_pressed(Action::NavigateLeft);
+    let right_key_pressed = actions.any_pressed(Action::NavigateRight);
+    let jump_key_just_pressed = actions.any_just_pressed(Action::Confirm);
+    let pause_key_just_pressed = actions.any_just_pressed(Action::Pause);
+
+    let (mut text, mut pause_menu) = match pause_menu_query.get_single_mut() {
+        Ok(q) => q,
+        Err(_) => return,
+    };
+
+    if pause_key_just_pressed {
+        app_state.set(AppState::Game);
+        return;
+    }
+
+    let item_count = pause_items(pause_menu.options_open, &settings, &strings).len();
+
+    pause_menu.rate_limit_selection.tick(time.delta());
+    let mut changed = false;
+    if pause_menu.rate_limit_selection.finished() {
+        if up_key_pressed && pause_menu.selection_index > 0 {
+            pause_menu.selection_index -= 1;
+            pause_menu.rate_limit_selection.reset();
+            changed = true;
+        } else if down_key_pressed && pause_menu.selection_index < item_count - 1 {
+            pause_menu.selection_index += 1;
+            pause_menu.rate_limit_selection.reset();
+            changed = true;
+        } else if pause_menu.options_open
+            && pause_menu.selection_index < 2
+            && (left_key_pressed || right_key_pressed)
+        {
+            let step = if left_key_pressed { -0.1 } else { 0.1 };
+            if pause_menu.selection_index == 0 {
+                settings.music_volume = step_volume(settings.music_volume, step);
+            } else {
+                settings.sfx_volume = step_volume(settings.sfx_volume, step);
+            }
+            pause_menu.rate_limit_selection.reset();
+            changed = true;
+        }
+    }
+
+    if jump_key_just_pressed {
+        match (pause_menu.options_open, pause_menu.selection_index) {
+            (false, 0) => app_state.set(AppState::Game),
+            (false, 1) => {
+                commands.insert_resource(RestartRound);
+                app_state.set(AppState::Game);
+            }
+            (false, 2) => {
+                pause_menu.options_open = true;
+                pause_menu.selection_index = 0;
+            }
+            (false, _) => app_state.set(AppState::Menu),
+            // Volumes change with left / right
+            (true, 0) | (true, 1) => {}
+            (true, 2) => settings.display = settings.display.next(),
+            (true, _) => {
+                pause_menu.options_open = false;
+                pause_menu.selection_index = 2;
+            }
+        }
+        changed = true;
+    }
+
+    if changed {
+        let style = text.sections[0].style.clone();
+        let items = pause_items(pause_menu.options_open, &settings, &strings);
+        text.sections = pause_sections(&strings, &items, pause_menu.selection_index, &style);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Cloud, Game};
+    use crate::headless::{headless_app, run, start_game, state, tap};
+    use crate::seed::{RunSeed, SeedMode};
+
+    #[test]
+    fn pausing_freezes_the_round() {
+        let mut app = headless_app();
+        app.world_mut().resource_mut::<RunSeed>().mode = SeedMode::Fixed(7);
+        start_game(&mut app);
+        run(&mut app, 300);
+
+        tap(&mut app, KeyCode::KeyP);
+        assert_eq!(state(&app), AppState::Pause);
+        let clouds = |app: &mut App| -> Vec<Vec3> {
+            app.world_mut()
+                .query_filtered::<&Transform, With<Cloud>>()
+                .iter(app.world())
+                .map(|t| t.translation)
+                .collect()
+        };
+        let paused_clouds = clouds(&mut app);
+        let game_entities = app
+            .world_mut()
+            .query_filtered::<Entity, With<Game>>()
+            .iter(app.world())
+            .count();
+        run(&mut app, 120);
+        assert!(!paused_clouds.is_empty());
+        assert_eq!(paused_clouds, clouds(&mut app));
+
+        tap(&mut app, KeyCode::KeyP);
+        assert_eq!(state(&app), AppState::Game);
+        assert_eq!(
+            app.world_mut()
+                .query_filtered::<Entity, With<Game>>()
+                .iter(app.world())
+                .count(),
+            game_entities
+        );
+        run(&mut app, 10);
+        assert_ne!(paused_clouds, clouds(&mut app));
+    }
+}
@@ -0,0 +1,418 @@
+use crate::game::{Difficulty, PlayMode};
+use crate::input::{nth_gamepad, ActionSet, InputBindings};
+use crate::menu::TotalSeconds;
+use crate::seed::{RunSeed, SeedMode};
+use crate::{launch_arg, AppState, InGame};
+use bevy::ecs::system::SystemParam;
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+const RECORDED_AXES: [GamepadAxisType; 4] = [
+    GamepadAxisType::LeftStickX,
+    GamepadAxisType::LeftStickY,
+    GamepadAxisType::RightStickX,
+    GamepadAxisType::RightStickY,
+];
+
+/// Input held during one frame of a game, for the keyboard and every player's gamepad.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ReplayFrame {
+    /// Frame length in seconds
+    pub delta: f32,
+    pub keys: Vec<KeyCode>,
+    /// The gamepad of each player in `InputBindings::players`, in the same order
+    #[serde(default)]
+    pub gamepads: Vec<GamepadFrame>,
+}
+
+/// Buttons and sticks of one gamepad during a frame.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct GamepadFrame {
+    pub buttons: Vec<GamepadButtonType>,
+    pub axes: Vec<(GamepadAxisType, f32)>,
+}
+
+/// A recorded game: everything needed to play it back frame by frame.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Replay {
+    pub seed: u64,
+    pub total_seconds: f32,
+    #[serde(default)]
+    pub play_mode: PlayMode,
+    #[serde(default)]
+    pub difficulty: Difficulty,
+    pub frames: Vec<ReplayFrame>,
+}
+
+/// Present when started with `--record <path>`; written when the game ends.
+#[derive(Resource)]
+pub struct ReplayRecorder {
+    path: String,
+    replay: Replay,
+}
+
+impl ReplayRecorder {
+    pub fn new(path: String) -> Self {
+        ReplayRecorder {
+            path,
+            replay: Replay::default(),
+        }
+    }
+}
+
+/// Present when started with `--replay <path>`; removed once every frame was fed back.
+#[derive(Resource)]
+pub struct ReplayPlayback {
+    replay: Replay,
+    frame: usize,
+    started: bool,
+}
+
+impl ReplayPlayback {
+    pub fn new(replay: Replay) -> Self {
+        ReplayPlayback {
+            replay,
+            frame: 0,
+            started: false,
+        }
+    }
+}
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        if launch_arg("--replay").is_some() {
+            app.add_systems(Startup, load_replay);
+        } else if let Some(path) = launch_arg("--record") {
+            app.insert_resource(ReplayRecorder::new(path));
+        }
+
+        app.add_systems(
+            Update,
+            (
+                start_playback.run_if(resource_exists::<ReplayPlayback>),
+                record_system
+                    .run_if(resource_exists::<ReplayRecorder>)
+                    .run_if(in_state(InGame)),
+            ),
+        )
+        // Recorded input stands in for the devices before it is mapped to actions. The first
+        // frame is fed before the state turns to `InGame`, as gameplay already runs on that frame.
+        .add_systems(
+            PreUpdate,
+            playback_system
+                .run_if(|playback: Option<Res<ReplayPlayback>>| playback.is_some_and(|p| p.started))
+                .after(InputSystem)
+                .before(ActionSet),
+        )
+        .add_systems(
+            OnEnter(InGame),
+            start_recording.run_if(resource_exists::<ReplayRecorder>),
+        )
+        .add_systems(
+            OnExit(InGame),
+            (
+                write_recording.run_if(resource_exists::<ReplayRecorder>),
+                stop_playback.run_if(resource_exists::<ReplayPlayback>),
+            ),
+        );
+    }
+}
+
+pub fn read_replay(path: &str) -> Result<Replay, String> {
+    let contents = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    ron::de::from_str(&contents).map_err(|e| e.to_string())
+}
+
+fn load_replay(mut commands: Commands) {
+    let path = launch_arg("--replay").unwrap_or_default();
+    match read_replay(&path) {
+        Ok(replay) => {
+            info!("Playing back {} frames from {}", replay.frames.len(), path);
+            commands.insert_resource(ReplayPlayback::new(replay));
+        }
+        Err(e) => error!("Could not read replay {}: {}", path, e),
+    }
+}
+
+fn start_recording(mut recorder: ResMut<ReplayRecorder>) {
+    recorder.replay = Replay::default();
+}
+
+/// Raw input devices, read while recording and written while playing back.
+#[derive(SystemParam)]
+struct GamepadInput<'w> {
+    bindings: Res<'w, InputBindings>,
+    gamepads: Res<'w, Gamepads>,
+    button_inputs: ResMut<'w, ButtonInput<GamepadButton>>,
+    axes: ResMut<'w, Axis<GamepadAxis>>,
+}
+
+impl GamepadInput<'_> {
+    /// The gamepad each player is bound to, in `InputBindings::players` order.
+    fn player_gamepads(&self) -> Vec<Gamepad> {
+        self.bindings
+            .players
+            .iter()
+            .map(|player| nth_gamepad(&self.gamepads, player.gamepad))
+            .collect()
+    }
+}
+
+fn record_system(
+    time: Res<Time>,
+    mut recorder: ResMut<ReplayRecorder>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    input: GamepadInput,
+) {
+    let gamepads = input
+        .player_gamepads()
+        .into_iter()
+        .map(|gamepad| GamepadFrame {
+            buttons: input
+                .button_inputs
+                .get_pressed()
+                .filter(|b| b.gamepad == gamepad)
+                .map(|b| b.button_type)
+                .collect(),
+            axes: RECORDED_AXES
+                .iter()
+                .filter_map(|axis| {
+                    input
+                        .axes
+                        .get(GamepadAxis::new(gamepad, *axis))
+                        .map(|value| (*axis, value))
+                })
+                .collect(),
+        })
+        .collect();
+    recorder.replay.frames.push(ReplayFrame {
+        delta: time.delta_seconds(),
+        keys: keyboard_input.get_pressed().copied().collect(),
+        gamepads,
+    });
+}
+
+fn write_recording(
+    mut recorder: ResMut<ReplayRecorder>,
+    run_seed: Res<RunSeed>,
+    total_seconds: Res<TotalSeconds>,
+    play_mode: Res<PlayMode>,
+    difficulty: Res<Difficulty>,
+) {
+    recorder.replay.seed = run_seed.seed;
+    recorder.replay.total_seconds = total_seconds.0;
+    recorder.replay.play_mode = *play_mode;
+    recorder.replay.difficulty = *difficulty;
+    let result = ron::ser::to_string(&recorder.replay)
+        .map_err(|e| e.to_string())
+        .and_then(|s| std::fs::write(&recorder.path, s).map_err(|e| e.to_string()));
+    match result {
+        Ok(_) => info!(
+            "Recorded {} frames to {}",
+            recorder.replay.frames.len(),
+            recorder.path
+        ),
+        Err(e) => error!("Could not write replay {}: {}", recorder.path, e),
+    }
+}
+
+/// Skips the menu and enters the game with the recorded seed, game length, play mode and
+/// difficulty.
+///
+/// Runs one frame ahead of the game so the first recorded frame length is already in effect.
+fn start_playback(
+    mut playback: ResMut<ReplayPlayback>,
+    mut run_seed: ResMut<RunSeed>,
+    mut total_seconds: ResMut<TotalSeconds>,
+    mut play_mode: ResMut<PlayMode>,
+    mut difficulty: ResMut<Difficulty>,
+    mut time_update_strategy: ResMut<TimeUpdateStrategy>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    if playback.started {
+        return;
+    }
+    playback.started = true;
+    run_seed.mode = SeedMode::Fixed(playback.replay.seed);
+    total_seconds.0 = playback.replay.total_seconds;
+    *play_mode = playback.replay.play_mode;
+    *difficulty = playback.replay.difficulty;
+    if let Some(frame) = playback.replay.frames.first() {
+        *time_update_strategy =
+            TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(frame.delta));
+    }
+    app_state.set(AppState::Game);
+}
+
+/// Hands time and input back to the player once the recorded game is over.
+fn stop_playback(
+    mut commands: Commands,
+    mut time_update_strategy: ResMut<TimeUpdateStrategy>,
+    playback: Res<ReplayPlayback>,
+) {
+    finish_playback(&mut commands, &mut time_update_strategy, &playback);
+}
+
+/// Hands time back to the clock and drops the replay.
+fn finish_playback(
+    commands: &mut Commands,
+    time_update_strategy: &mut TimeUpdateStrategy,
+    playback: &ReplayPlayback,
+) {
+    info!("Replay finished after {} frames", playback.frame);
+    *time_update_strategy = TimeUpdateStrategy::Automatic;
+    commands.remove_resource::<ReplayPlayback>();
+}
+
+fn playback_system(
+    mut commands: Commands,
+    mut playback: ResMut<ReplayPlayback>,
+    mut time_update_strategy: ResMut<TimeUpdateStrategy>,
+    mut keyboard_input: ResMut<ButtonInput<KeyCode>>,
+    mut input: GamepadInput,
+) {
+    let index = playback.frame;
+    let (frame, previous) = match playback.replay.frames.get(index) {
+        Some(f) => (
+            f.clone(),
+            index
+                .checked_sub(1)
+                .and_then(|i| playback.replay.frames.get(i))
+                .cloned()
+                .unwrap_or_default(),
+        ),
+        None => {
+            finish_playback(&mut commands, &mut time_update_strategy, &playback);
+            return;
+        }
+    };
+
+    // Rebuild pressed / just_pressed / just_released from the recorded frames only
+    keyboard_input.reset_all();
+    for key in &frame.keys {
+        keyboard_input.press(*key);
+        if previous.keys.contains(key) {
+            keyboard_input.clear_just_pressed(*key);
+        }
+    }
+    for key in previous.keys.iter().filter(|k| !frame.keys.contains(k)) {
+        keyboard_input.press(*key);
+        keyboard_input.release(*key);
+        keyboard_input.clear_just_pressed(*key);
+    }
+
+    input.button_inputs.reset_all();
+    for (player, gamepad) in input.player_gamepads().into_iter().enumerate() {
+        let pad = frame.gamepads.get(player).cloned().unwrap_or_default();
+        let previous_pad = previous.gamepads.get(player).cloned().unwrap_or_default();
+        for button_type in &pad.buttons {
+            let button = GamepadButton::new(gamepad, *button_type);
+            input.button_inputs.press(button);
+            if previous_pad.buttons.contains(button_type) {
+                input.button_inputs.clear_just_pressed(button);
+            }
+        }
+        for button_type in previous_pad
+            .buttons
+            .iter()
+            .filter(|b| !pad.buttons.contains(b))
+        {
+            let button = GamepadButton::new(gamepad, *button_type);
+            input.button_inputs.press(button);
+            input.button_inputs.release(button);
+            input.button_inputs.clear_just_pressed(button);
+        }
+
+        for axis in RECORDED_AXES {
+            let value = pad
+                .axes
+                .iter()
+                .find(|(a, _)| *a == axis)
+                .map(|(_, v)| *v)
+                .unwrap_or(0.0);
+            input.axes.set(GamepadAxis::new(gamepad, axis), value);
+        }
+    }
+
+    playback.frame += 1;
+    if let Some(next) = playback.replay.frames.get(playback.frame) {
+        *time_update_strategy =
+            TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(next.delta));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Player, TotalScore};
+    use crate::headless::{headless_app, load_level, press, release, run, start_game, state, tap};
+    use crate::settings::Settings;
+
+    /// Steps through the rest of the round and returns where both characters ended up and the
+    /// score, as of its last frame.
+    fn finish_round(app: &mut App) -> (Vec<Vec3>, u32) {
+        let mut last = (Vec::new(), 0);
+        for _ in 0..1000 {
+            if state(app) != AppState::Game {
+                return last;
+            }
+            let mut players = app.world_mut().query::<(&Player, &Transform)>();
+            let mut positions = players
+                .iter(app.world())
+                .map(|(p, t)| (p.0, t.translation))
+                .collect::<Vec<_>>();
+            positions.sort_by_key(|(p, _)| *p);
+            last = (
+                positions.into_iter().map(|(_, t)| t).collect(),
+                app.world().resource::<TotalScore>().0,
+            );
+            app.update();
+        }
+        panic!("round did not end");
+    }
+
+    #[test]
+    fn replay_plays_back_both_players() {
+        let path = std::env::temp_dir().join(format!("replay-{}.ron", std::process::id()));
+        let mut app = headless_app();
+        app.insert_resource(PlayMode::Coop)
+            .insert_resource(ReplayRecorder::new(path.display().to_string()));
+        app.world_mut().resource_mut::<RunSeed>().mode = SeedMode::Fixed(7);
+        app.world_mut().resource_mut::<Settings>().total_seconds = 1.0;
+        start_game(&mut app);
+
+        // Player 1 on the keyboard, player 2 on the second gamepad
+        let pad_left = GamepadButton::new(Gamepad::new(1), GamepadButtonType::DPadLeft);
+        press(&mut app, KeyCode::KeyD);
+        app.world_mut()
+            .resource_mut::<ButtonInput<GamepadButton>>()
+            .press(pad_left);
+        run(&mut app, 30);
+        release(&mut app, KeyCode::KeyD);
+        app.world_mut()
+            .resource_mut::<ButtonInput<GamepadButton>>()
+            .release(pad_left);
+        tap(&mut app, KeyCode::Space);
+        let recorded = finish_round(&mut app);
+        assert!(recorded.0[0].x > 300.0);
+        assert!(recorded.0[1].x < -200.0);
+
+        let replay = read_replay(&path.display().to_string()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let mut app = headless_app();
+        load_level(&mut app);
+        app.insert_resource(ReplayPlayback::new(replay));
+        for _ in 0..10 {
+            if state(&app) == AppState::Game {
+                break;
+            }
+            app.update();
+        }
+        assert_eq!(finish_round(&mut app), recorded);
+    }
+}
@@ -0,0 +1,253 @@
+use crate::game::{Game, GameplaySet};
+use crate::locale::{Strings, FONT};
+use crate::{AppState, InGame};
+use bevy::prelude::*;
+
+/// Offerings of at least this many drops earn a percentage of their base score on top,
+/// checked in order.
+const SIZE_BONUS: [(u32, u32); 2] = [(20, 100), (10, 50)];
+/// Percentage of the base score added when both characters bring drops.
+const TEAM_BONUS: u32 = 50;
+/// Percentage of the base score added for each earlier offering in the streak.
+const STREAK_BONUS: u32 = 25;
+/// Earlier offerings that count towards the streak bonus at most.
+const MAX_STREAK: u32 = 4;
+/// Round time after an offering within which the next one continues the streak.
+const STREAK_SECONDS: f32 = 60.0;
+const POPUP_SECONDS: f32 = 3.0;
+/// How fast the score popup drifts upwards, in pixels per second.
+const POPUP_RISE: f32 = 10.0;
+
+/// How the score of one offering was put together.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ScoreBreakdown {
+    /// Drops times the shrine's multiplier
+    pub base: u32,
+    pub size_bonus: u32,
+    pub team_bonus: u32,
+    pub streak_bonus: u32,
+    /// Offerings in a row, this one included
+    pub streak: u32,
+}
+
+impl ScoreBreakdown {
+    /// Scores an offering of `drops` worth `base` points, brought by `offerers` characters as the
+    /// `streak`th offering in a row.
+    pub fn new(base: u32, drops: u32, offerers: usize, streak: u32) -> Self {
+        let percent = |p: u32| base * p / 100;
+        let size = SIZE_BONUS
+            .iter()
+            .find(|(min, _)| drops >= *min)
+            .map(|(_, p)| *p)
+            .unwrap_or(0);
+        let team = if offerers > 1 { TEAM_BONUS } else { 0 };
+        ScoreBreakdown {
+            base,
+            size_bonus: percent(size),
+            team_bonus: percent(team),
+            streak_bonus: percent(STREAK_BONUS * streak.saturating_sub(1).min(MAX_STREAK)),
+            streak,
+        }
+    }
+
+    pub fn total(&self) -> u32 {
+        self.base + self.size_bonus + self.team_bonus + self.streak_bonus
+    }
+
+    /// One line per part that scored, ending with the total.
+    pub fn lines(&self, strings: &Strings) -> Vec<String> {
+        let mut lines = vec![strings.format("score.base", &[("points", &self.base)])];
+        if self.size_bonus > 0 {
+            lines.push(strings.format("score.size", &[("points", &self.size_bonus)]));
+        }
+        if self.team_bonus > 0 {
+            lines.push(strings.format("score.team", &[("points", &self.team_bonus)]));
+        }
+        if self.streak_bonus > 0 {
+            lines.push(strings.format(
+                "score.streak",
+                &[("count", &self.streak), ("points", &self.streak_bonus)],
+            ));
+        }
+        lines.push(strings.format("score.total", &[("points", &self.total())]));
+        lines
+    }
+}
+
+/// Offerings made in quick succession, reset when a game starts.
+#[derive(Resource)]
+pub struct OfferingStreak {
+    pub count: u32,
+    window: Timer,
+}
+
+impl Default for OfferingStreak {
+    fn default() -> Self {
+        OfferingStreak {
+            count: 0,
+            window: Timer::from_seconds(STREAK_SECONDS, TimerMode::Once),
+        }
+    }
+}
+
+impl OfferingStreak {
+    /// Records an offering and returns how many were made in a row.
+    pub fn offer(&mut self) -> u32 {
+        if self.window.finished() {
+            self.count = 0;
+        }
+        self.count += 1;
+        self.window.reset();
+        self.count
+    }
+}
+
+/// Breakdown of the last offering's score, shown under the HUD for a few seconds.
+#[derive(Component)]
+pub struct ScorePopup(Timer);
+
+pub fn spawn_score_popup(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    strings: &Strings,
+    breakdown: &ScoreBreakdown,
+) {
+    commands.spawn((
+        Game,
+        ScorePopup(Timer::from_seconds(POPUP_SECONDS, TimerMode::Once)),
+        TextBundle::from_section(
+            breakdown.lines(strings).join("\n\n"),
+            TextStyle {
+                font: asset_server.load(FONT),
+                font_size: 14.0,
+                color: Color::srgb(0.6, 0.85, 1.0),
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Percent(22.0),
+            left: Val::Percent(3.0),
+            ..default()
+        }),
+    ));
+}
+
+pub struct ScoringPlugin;
+
+impl Plugin for ScoringPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<OfferingStreak>()
+            .add_systems(OnEnter(InGame), reset_streak)
+            .add_systems(
+                Update,
+                (offering_streak_system, score_popup_system)
+                    .in_set(GameplaySet)
+                    .run_if(in_state(AppState::Game)),
+            );
+    }
+}
+
+fn reset_streak(mut streak: ResMut<OfferingStreak>) {
+    *streak = OfferingStreak::default();
+}
+
+fn offering_streak_system(time: Res<Time>, mut streak: ResMut<OfferingStreak>) {
+    streak.window.tick(time.delta());
+}
+
+/// Floats the popup upwards while it fades out.
+fn score_popup_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut popup_query: Query<(Entity, &mut ScorePopup, &mut Style, &mut Text)>,
+) {
+    for (entity, mut popup, mut style, mut text) in popup_query.iter_mut() {
+        popup.0.tick(time.delta());
+        if popup.0.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        style.margin.top = Val::Px(-POPUP_RISE * popup.0.elapsed_secs());
+        let alpha = popup.0.fraction_remaining();
+        for section in text.sections.iter_mut() {
+            section.style.color.set_alpha(alpha);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Player, TotalScore, WaterCollection};
+    use crate::headless::{headless_app, offer_at_tlaloc, start_game};
+
+    #[test]
+    fn small_solo_offering_scores_its_base() {
+        let breakdown = ScoreBreakdown::new(5, 5, 1, 1);
+        assert_eq!(breakdown.total(), 5);
+        assert_eq!(
+            breakdown,
+            ScoreBreakdown {
+                base: 5,
+                streak: 1,
+                ..default()
+            }
+        );
+    }
+
+    #[test]
+    fn bonuses_are_shares_of_the_base() {
+        let breakdown = ScoreBreakdown::new(24, 12, 2, 2);
+        assert_eq!(breakdown.size_bonus, 12);
+        assert_eq!(breakdown.team_bonus, 12);
+        assert_eq!(breakdown.streak_bonus, 6);
+        assert_eq!(breakdown.total(), 54);
+        // The biggest size bonus that applies wins
+        assert_eq!(ScoreBreakdown::new(20, 20, 1, 1).size_bonus, 20);
+    }
+
+    #[test]
+    fn streak_bonus_stops_growing() {
+        let capped = ScoreBreakdown::new(100, 1, 1, MAX_STREAK + 1).streak_bonus;
+        assert_eq!(capped, STREAK_BONUS * MAX_STREAK);
+        assert_eq!(ScoreBreakdown::new(100, 1, 1, 20).streak_bonus, capped);
+    }
+
+    #[test]
+    fn streak_ends_when_the_window_runs_out() {
+        let mut streak = OfferingStreak::default();
+        assert_eq!(streak.offer(), 1);
+        assert_eq!(streak.offer(), 2);
+        streak
+            .window
+            .tick(std::time::Duration::from_secs_f32(STREAK_SECONDS));
+        assert_eq!(streak.offer(), 1);
+    }
+
+    #[test]
+    fn offering_together_earns_bonuses() {
+        let mut app = headless_app();
+        start_game(&mut app);
+
+        let mut players = app.world_mut().query::<(&Player, &mut Transform)>();
+        for (player, mut transform) in players.iter_mut(app.world_mut()) {
+            if player.0 == 2 {
+                transform.translation = Vec3::new(60.0, 1205.0, 11.0);
+            }
+        }
+        app.world_mut()
+            .resource_mut::<WaterCollection>()
+            .total_player2 = 6;
+        offer_at_tlaloc(&mut app, 6);
+
+        // 12 drops, half again for a big offering and half again for bringing them together
+        assert_eq!(app.world().resource::<TotalScore>().0, 24);
+        assert_eq!(app.world().resource::<OfferingStreak>().count, 1);
+        let mut popups = app.world_mut().query_filtered::<&Text, With<ScorePopup>>();
+        let popup = popups.single(app.world()).sections[0].value.clone();
+        assert_eq!(
+            popup,
+            "Drops +12\n\nBig offering +6\n\nTogether +6\n\nTotal +24"
+        );
+    }
+}
@@ -0,0 +1,113 @@
+use crate::{launch_arg, InGame};
+use bevy::prelude::*;
+use bevy_prng::ChaCha8Rng;
+use bevy_rand::resource::GlobalEntropy;
+use rand::{Rng, SeedableRng};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_DAY: u64 = 60 * 60 * 24;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum SeedMode {
+    /// A new seed every game
+    #[default]
+    Random,
+    /// The same seed for everyone on the same (UTC) day
+    Daily,
+    /// Set with `--seed <number>`
+    Fixed(u64),
+}
+
+impl SeedMode {
+    /// String ID of the mode's name, or the seed itself
+    pub fn label(&self) -> String {
+        match self {
+            SeedMode::Random => String::from("seed.random"),
+            SeedMode::Daily => String::from("seed.daily"),
+            SeedMode::Fixed(seed) => seed.to_string(),
+        }
+    }
+
+    /// Cycles Random -> Daily -> Random, keeping a fixed seed in the rotation if one was given.
+    pub fn next(&self, fixed: Option<u64>) -> SeedMode {
+        match (self, fixed) {
+            (SeedMode::Random, _) => SeedMode::Daily,
+            (SeedMode::Daily, Some(seed)) => SeedMode::Fixed(seed),
+            _ => SeedMode::Random,
+        }
+    }
+}
+
+/// Seed of the `GlobalEntropy<ChaCha8Rng>` shared by every spawner, picked when a game starts.
+#[derive(Resource, Default)]
+pub struct RunSeed {
+    pub mode: SeedMode,
+    /// Seed given on the command line, if any
+    pub fixed: Option<u64>,
+    /// Seed used by the current (or last) game
+    pub seed: u64,
+}
+
+pub struct SeedPlugin;
+
+impl Plugin for SeedPlugin {
+    fn build(&self, app: &mut App) {
+        let fixed = launch_arg("--seed").and_then(|s| s.parse::<u64>().ok());
+        let mode = match fixed {
+            Some(seed) => SeedMode::Fixed(seed),
+            None if std::env::args().any(|arg| arg == "--daily") => SeedMode::Daily,
+            None => SeedMode::Random,
+        };
+        app.insert_resource(RunSeed {
+            mode,
+            fixed,
+            seed: 0,
+        })
+        .add_systems(OnEnter(InGame), reseed);
+    }
+}
+
+fn daily_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() / SECONDS_PER_DAY)
+        .unwrap_or(0)
+}
+
+pub(crate) fn reseed(mut run_seed: ResMut<RunSeed>, mut rng: ResMut<GlobalEntropy<ChaCha8Rng>>) {
+    run_seed.seed = match run_seed.mode {
+        SeedMode::Random => rand::thread_rng().gen(),
+        SeedMode::Daily => daily_seed(),
+        SeedMode::Fixed(seed) => seed,
+    };
+    info!("Game seed {}", run_seed.seed);
+    *rng = GlobalEntropy::<ChaCha8Rng>::seed_from_u64(run_seed.seed);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Cloud;
+    use crate::headless::{headless_app, run, start_game};
+
+    fn cloud_positions(seed: u64) -> Vec<Vec3> {
+        let mut app = headless_app();
+        app.world_mut().resource_mut::<RunSeed>().mode = SeedMode::Fixed(seed);
+        start_game(&mut app);
+        run(&mut app, 600);
+
+        app.world_mut()
+            .query_filtered::<&Transform, With<Cloud>>()
+            .iter(app.world())
+            .map(|t| t.translation)
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_spawns_same_clouds() {
+        let first = cloud_positions(7);
+        assert!(!first.is_empty());
+        assert_eq!(first, cloud_positions(7));
+        assert_ne!(first, cloud_positions(8));
+    }
+}
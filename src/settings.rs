@@ -0,0 +1,210 @@
+use crate::input::InputBindings;
+use crate::locale::{Language, Strings};
+use crate::menu::TotalSeconds;
+use crate::storage::{FileStorage, Storage};
+use crate::{launch_arg, Music};
+use bevy::audio::Volume;
+use bevy::prelude::*;
+use bevy::window::{PrimaryWindow, WindowMode};
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_SETTINGS_PATH: &str = "settings.ron";
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, Serialize, Deserialize)]
+pub enum DisplayMode {
+    #[default]
+    Windowed,
+    Fullscreen,
+}
+
+impl DisplayMode {
+    /// String ID of the mode's name
+    pub fn label(&self) -> &'static str {
+        match self {
+            DisplayMode::Windowed => "display.windowed",
+            DisplayMode::Fullscreen => "display.fullscreen",
+        }
+    }
+
+    pub fn next(&self) -> DisplayMode {
+        match self {
+            DisplayMode::Windowed => DisplayMode::Fullscreen,
+            DisplayMode::Fullscreen => DisplayMode::Windowed,
+        }
+    }
+}
+
+/// Everything kept between launches. Loaded before `Startup` and saved whenever it changes.
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// 0.0 to 1.0
+    pub music_volume: f32,
+    /// 0.0 to 1.0, applied to every sound started afterwards
+    pub sfx_volume: f32,
+    pub display: DisplayMode,
+    /// Last game length picked in the menu
+    pub total_seconds: f32,
+    pub bindings: InputBindings,
+    pub language: Language,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            music_volume: 1.0,
+            sfx_volume: 1.0,
+            display: DisplayMode::Windowed,
+            total_seconds: 180.0,
+            bindings: InputBindings::default(),
+            language: Language::default(),
+        }
+    }
+}
+
+/// The storage in use, `settings.ron` in the working directory unless `--settings <path>` is
+/// given. Insert one before adding `SettingsPlugin` to replace the default.
+#[derive(Resource)]
+pub struct SettingsBackend(pub Box<dyn Storage>);
+
+impl Default for SettingsBackend {
+    #[cfg(not(target_arch = "wasm32"))]
+    fn default() -> Self {
+        SettingsBackend(Box::new(FileStorage {
+            path: launch_arg("--settings").unwrap_or_else(|| String::from(DEFAULT_SETTINGS_PATH)),
+        }))
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn default() -> Self {
+        SettingsBackend(Box::new(crate::storage::MemoryStorage::default()))
+    }
+}
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SettingsBackend>()
+            .init_resource::<Settings>()
+            .add_systems(PreStartup, load_settings)
+            .add_systems(
+                Update,
+                (save_settings_system, apply_settings_system).chain(),
+            );
+    }
+}
+
+fn load_settings(
+    backend: Res<SettingsBackend>,
+    mut settings: ResMut<Settings>,
+    mut bindings: ResMut<InputBindings>,
+    mut total_seconds: ResMut<TotalSeconds>,
+    mut strings: ResMut<Strings>,
+) {
+    match backend.0.load() {
+        Ok(Some(contents)) => match ron::de::from_str::<Settings>(&contents) {
+            Ok(s) => {
+                info!("Loaded settings");
+                *settings = s;
+            }
+            Err(e) => error!("Could not parse settings: {}", e),
+        },
+        Ok(None) => {}
+        Err(e) => error!("Could not read settings: {}", e),
+    }
+    *bindings = settings.bindings.clone();
+    bindings.add_missing_actions();
+    total_seconds.0 = settings.total_seconds;
+    strings.language = settings.language;
+}
+
+/// Copies bindings, game length and language into `Settings` and writes them out when anything
+/// differs.
+fn save_settings_system(
+    backend: Res<SettingsBackend>,
+    mut settings: ResMut<Settings>,
+    bindings: Res<InputBindings>,
+    total_seconds: Res<TotalSeconds>,
+    strings: Res<Strings>,
+    mut saved: Local<Option<Settings>>,
+) {
+    if bindings.is_changed() && settings.bindings != *bindings {
+        settings.bindings = bindings.clone();
+    }
+    if total_seconds.is_changed() && settings.total_seconds != total_seconds.0 {
+        settings.total_seconds = total_seconds.0;
+    }
+    if strings.is_changed() && settings.language != strings.language {
+        settings.language = strings.language;
+    }
+
+    let last = match saved.as_ref() {
+        Some(s) => s,
+        None => {
+            // What was just loaded is already on disk
+            *saved = Some(settings.clone());
+            return;
+        }
+    };
+    if !settings.is_changed() || *last == *settings {
+        return;
+    }
+
+    let result = ron::ser::to_string_pretty(&*settings, ron::ser::PrettyConfig::default())
+        .map_err(|e| e.to_string())
+        .and_then(|s| backend.0.save(&s));
+    match result {
+        Ok(_) => info!("Saved settings"),
+        Err(e) => error!("Could not save settings: {}", e),
+    }
+    *saved = Some(settings.clone());
+}
+
+fn apply_settings_system(
+    settings: Res<Settings>,
+    mut global_volume: ResMut<GlobalVolume>,
+    music_query: Query<Ref<AudioSink>, With<Music>>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    // The sink only exists once the music has loaded
+    for sink in &music_query {
+        if settings.is_changed() || sink.is_added() {
+            sink.set_volume(settings.music_volume);
+        }
+    }
+    if !settings.is_changed() {
+        return;
+    }
+    global_volume.volume = Volume::new(settings.sfx_volume);
+    for mut window in &mut window_query {
+        window.mode = match settings.display {
+            DisplayMode::Windowed => WindowMode::Windowed,
+            DisplayMode::Fullscreen => WindowMode::BorderlessFullscreen,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::headless::{headless_app, run};
+    use crate::storage::MemoryStorage;
+
+    #[test]
+    fn settings_survive_a_restart() {
+        let storage = MemoryStorage::default();
+        let mut app = headless_app();
+        app.insert_resource(SettingsBackend(Box::new(storage.clone())));
+        run(&mut app, 2);
+        app.insert_resource(TotalSeconds(240.0));
+        app.world_mut().resource_mut::<Settings>().music_volume = 0.5;
+        run(&mut app, 2);
+
+        let mut app = headless_app();
+        app.insert_resource(SettingsBackend(Box::new(storage)));
+        run(&mut app, 1);
+        assert_eq!(app.world().resource::<TotalSeconds>().0, 240.0);
+        assert_eq!(app.world().resource::<Settings>().music_volume, 0.5);
+    }
+}
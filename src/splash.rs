@@ -1,4 +1,5 @@
 use crate::despawn_screen;
+use crate::locale::Strings;
 use crate::AppState;
 use bevy::{prelude::*, render::view::RenderLayers};
 
@@ -25,6 +26,7 @@ fn splash_setup(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     mut bg: ResMut<ClearColor>,
+    strings: Res<Strings>,
 ) {
     bg.0 = Color::BLACK;
     let icon = asset_server.load("bevylogo.png");
@@ -72,7 +74,7 @@ fn splash_setup(
         RenderLayers::layer(2),
         OnSplashScreen,
         TextBundle::from_section(
-            "Made with Bevy",
+            strings.get("splash.made_with"),
             TextStyle {
                 font: asset_server.load("fonts/PressStart2P-vaV7.ttf"),
                 font_size: 52.0,
@@ -0,0 +1,168 @@
+use crate::game::{GameplaySet, Player, PlayerDialog};
+use crate::locale::Strings;
+use crate::{AppState, InGame};
+use bevy::prelude::*;
+
+/// Height of a character standing on the ground, altitudes are measured from here.
+pub const GROUND_HEIGHT: f32 = -700.0;
+/// The physics run at 100 pixels per meter.
+const PIXELS_PER_METER: f32 = 100.0;
+/// Width of the row labels in the summary, PressStart2P is monospaced.
+const LABEL_COLUMNS: usize = 18;
+const VALUE_COLUMNS: usize = 8;
+
+/// What one character did during a round.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PlayerStats {
+    pub collected: u32,
+    /// Drops taken without scoring: handed to an NPC that does not score them (see
+    /// `NpcRole::Cleanser`), or dropped by an offering made while out of the offering zone
+    pub lost: u32,
+    /// Highest point the character reached, in pixels
+    pub highest: f32,
+    pub dialog_seconds: f32,
+    pub jumps: u32,
+    /// Scoring offerings made from inside the offering zone
+    pub offerings: u32,
+    /// Most of the character's own drops handed over in a single scoring offering
+    pub biggest_offering: u32,
+}
+
+/// Stats of the current (or last) round, kept for the GameOver summary.
+#[derive(Resource, Debug, Default)]
+pub struct RoundStats {
+    /// Nata's and Nena's stats, in `Player` order
+    pub players: [PlayerStats; 2],
+}
+
+impl RoundStats {
+    pub fn player(&mut self, player: &Player) -> &mut PlayerStats {
+        &mut self.players[player.0 - 1]
+    }
+
+    /// The summary as a table with a column per character.
+    pub fn summary_text(&self, strings: &Strings) -> String {
+        let row = |label: &str, nata: String, nena: String| {
+            format!(
+                "{:<label$}{:>value$}{:>value$}",
+                label,
+                nata,
+                nena,
+                label = LABEL_COLUMNS,
+                value = VALUE_COLUMNS
+            )
+        };
+        let per_player = |id: &str, value: &dyn Fn(&PlayerStats) -> String| {
+            // `Player(2)` is Nata, shown first like in the HUD
+            row(
+                strings.get(id),
+                value(&self.players[1]),
+                value(&self.players[0]),
+            )
+        };
+        [
+            row(
+                "",
+                Player(2).name().to_string(),
+                Player(1).name().to_string(),
+            ),
+            per_player("summary.collected", &|s| s.collected.to_string()),
+            per_player("summary.lost", &|s| s.lost.to_string()),
+            per_player("summary.altitude", &|s| {
+                format!(
+                    "{}m",
+                    ((s.highest - GROUND_HEIGHT).max(0.0) / PIXELS_PER_METER) as u32
+                )
+            }),
+            per_player("summary.dialog", &|s| {
+                format!("{}s", s.dialog_seconds as u32)
+            }),
+            per_player("summary.jumps", &|s| s.jumps.to_string()),
+            per_player("summary.offerings", &|s| s.offerings.to_string()),
+            per_player("summary.biggest", &|s| s.biggest_offering.to_string()),
+        ]
+        .join("\n")
+    }
+}
+
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RoundStats>()
+            .add_systems(OnEnter(InGame), reset_stats)
+            .add_systems(
+                Update,
+                stats_tracking_system
+                    .in_set(GameplaySet)
+                    .run_if(in_state(AppState::Game)),
+            );
+    }
+}
+
+fn reset_stats(mut stats: ResMut<RoundStats>) {
+    *stats = RoundStats::default();
+    for player in stats.players.iter_mut() {
+        player.highest = GROUND_HEIGHT;
+    }
+}
+
+/// Keeps track of the stats that change continuously rather than on events.
+fn stats_tracking_system(
+    time: Res<Time>,
+    mut stats: ResMut<RoundStats>,
+    player_query: Query<(&Player, &Transform, &PlayerDialog)>,
+) {
+    for (player, transform, dialog) in player_query.iter() {
+        let player_stats = stats.player(player);
+        player_stats.highest = player_stats.highest.max(transform.translation.y);
+        if dialog.is_open() {
+            player_stats.dialog_seconds += time.delta_seconds();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gameover::RoundSummary;
+    use crate::headless::{
+        active_player, headless_app, offer_at_chalchiuhtlicue, run, spawn_drop, start_game, state,
+    };
+    use crate::settings::Settings;
+
+    #[test]
+    fn round_summary_lists_each_players_drops() {
+        let mut app = headless_app();
+        app.world_mut().resource_mut::<Settings>().total_seconds = 1.0;
+        start_game(&mut app);
+
+        let player = active_player(&mut app);
+        let position = app.world().get::<Transform>(player).unwrap().translation;
+        spawn_drop(&mut app, position);
+        run(&mut app, 3);
+        offer_at_chalchiuhtlicue(&mut app);
+
+        run(&mut app, 70);
+        assert_eq!(state(&app), AppState::GameOver);
+        let mut summaries = app
+            .world_mut()
+            .query_filtered::<&Text, With<RoundSummary>>();
+        let summary = summaries.single(app.world()).sections[0].value.clone();
+        let lines = summary.lines().collect::<Vec<_>>();
+        assert_eq!(
+            lines[0].split_whitespace().collect::<Vec<_>>(),
+            ["Nata", "Nena"]
+        );
+        assert_eq!(
+            lines[1],
+            format!("{:<18}{:>8}{:>8}", "Drops collected", 0, 1)
+        );
+        assert_eq!(lines[2], format!("{:<18}{:>8}{:>8}", "Drops lost", 0, 1));
+        assert_eq!(lines[6], format!("{:<18}{:>8}{:>8}", "Offerings", 0, 0));
+        assert_eq!(
+            lines[7],
+            format!("{:<18}{:>8}{:>8}", "Biggest offering", 0, 0)
+        );
+    }
+}
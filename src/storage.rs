@@ -0,0 +1,44 @@
+use std::sync::{Arc, Mutex};
+
+/// Where something kept between launches, like settings or high scores, is read from and
+/// written to.
+pub trait Storage: Send + Sync + 'static {
+    /// Returns `None` when nothing was saved yet.
+    fn load(&self) -> Result<Option<String>, String>;
+    fn save(&self, contents: &str) -> Result<(), String>;
+}
+
+/// A file at `path`, relative to the working directory unless absolute.
+pub struct FileStorage {
+    pub path: String,
+}
+
+impl Storage for FileStorage {
+    fn load(&self) -> Result<Option<String>, String> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(contents) => Ok(Some(contents)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    fn save(&self, contents: &str) -> Result<(), String> {
+        std::fs::write(&self.path, contents).map_err(|e| e.to_string())
+    }
+}
+
+/// Keeps everything for the current session only. Used on wasm until browser storage is wired up.
+#[cfg_attr(not(any(test, target_arch = "wasm32")), allow(dead_code))]
+#[derive(Clone, Default)]
+pub struct MemoryStorage(pub Arc<Mutex<Option<String>>>);
+
+impl Storage for MemoryStorage {
+    fn load(&self) -> Result<Option<String>, String> {
+        Ok(self.0.lock().map_err(|e| e.to_string())?.clone())
+    }
+
+    fn save(&self, contents: &str) -> Result<(), String> {
+        *self.0.lock().map_err(|e| e.to_string())? = Some(contents.to_string());
+        Ok(())
+    }
+}
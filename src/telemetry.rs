@@ -0,0 +1,293 @@
+use crate::game::{GameEvent, GameplaySet, TotalTime};
+use crate::{launch_arg, InGame};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+
+/// Height of the bands drops are grouped by in the summary, in pixels.
+const HEIGHT_BAND: f32 = 250.0;
+/// Carried drops that jumps are grouped by in the summary, see `player_gravity_system`.
+const WATER_BANDS: [u32; 4] = [0, 1, 5, 10];
+
+/// One line of a telemetry file.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TelemetryRecord {
+    /// Seconds into the round, the clock stops while a dialog is open
+    pub time: f32,
+    #[serde(flatten)]
+    pub event: GameEvent,
+}
+
+/// Present when started with `--telemetry <path>`; every game's events are appended to the file
+/// as JSON Lines.
+#[derive(Resource)]
+pub struct TelemetryRecorder {
+    path: String,
+    writer: Option<BufWriter<File>>,
+}
+
+impl TelemetryRecorder {
+    pub fn new(path: String) -> Self {
+        TelemetryRecorder { path, writer: None }
+    }
+}
+
+pub struct TelemetryPlugin;
+
+impl Plugin for TelemetryPlugin {
+    fn build(&self, app: &mut App) {
+        if let Some(path) = launch_arg("--telemetry") {
+            app.insert_resource(TelemetryRecorder::new(path));
+        }
+
+        app.add_systems(
+            OnEnter(InGame),
+            open_telemetry.run_if(resource_exists::<TelemetryRecorder>),
+        )
+        // The last events of a round are sent as it ends, so this runs in every state
+        .add_systems(
+            Update,
+            telemetry_system
+                .run_if(resource_exists::<TelemetryRecorder>)
+                .after(GameplaySet),
+        )
+        .add_systems(
+            OnExit(InGame),
+            close_telemetry.run_if(resource_exists::<TelemetryRecorder>),
+        );
+    }
+}
+
+fn open_telemetry(mut recorder: ResMut<TelemetryRecorder>) {
+    match OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&recorder.path)
+    {
+        Ok(file) => recorder.writer = Some(BufWriter::new(file)),
+        Err(e) => error!("Could not open telemetry file {}: {}", recorder.path, e),
+    }
+}
+
+fn telemetry_system(
+    mut recorder: ResMut<TelemetryRecorder>,
+    total_time: Res<TotalTime>,
+    mut game_events: EventReader<GameEvent>,
+) {
+    let writer = match recorder.writer.as_mut() {
+        Some(w) => w,
+        None => {
+            game_events.clear();
+            return;
+        }
+    };
+    for event in game_events.read() {
+        let record = TelemetryRecord {
+            time: total_time.elapsed_secs(),
+            event: event.clone(),
+        };
+        let result = serde_json::to_string(&record)
+            .map_err(|e| e.to_string())
+            .and_then(|line| writeln!(writer, "{}", line).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            error!("Could not write telemetry: {}", e);
+        }
+    }
+}
+
+fn close_telemetry(mut recorder: ResMut<TelemetryRecorder>) {
+    if let Some(mut writer) = recorder.writer.take() {
+        if let Err(e) = writer.flush() {
+            error!("Could not write telemetry file {}: {}", recorder.path, e);
+        }
+    }
+}
+
+/// Totals over any number of telemetry files, printed by `--telemetry-summary <paths>...`.
+#[derive(Debug, Default)]
+pub struct TelemetrySummary {
+    pub games: u32,
+    pub final_scores: Vec<u32>,
+    pub drops_spawned: u32,
+    pub drops_collected: u32,
+    pub drops_expired: u32,
+    /// Spawned and collected drops by height band
+    pub drops_by_height: BTreeMap<i32, (u32, u32)>,
+    /// Jumps by the lowest drop count of their `WATER_BANDS` band
+    pub jumps_by_water: BTreeMap<u32, u32>,
+    pub switches: u32,
+    /// Drops and score of every offering, by NPC
+    pub offerings: BTreeMap<String, Vec<(u32, u32)>>,
+    pub resets: u32,
+}
+
+impl TelemetrySummary {
+    pub fn add(&mut self, record: &TelemetryRecord) {
+        let band = |position: &Vec2| (position.y / HEIGHT_BAND).floor() as i32;
+        match &record.event {
+            GameEvent::Start { .. } => self.games += 1,
+            GameEvent::DropSpawned { position } => {
+                self.drops_spawned += 1;
+                self.drops_by_height.entry(band(position)).or_default().0 += 1;
+            }
+            GameEvent::DropCollected { position, .. } => {
+                self.drops_collected += 1;
+                self.drops_by_height.entry(band(position)).or_default().1 += 1;
+            }
+            GameEvent::DropExpired { .. } => self.drops_expired += 1,
+            GameEvent::Jump { water, .. } => {
+                let band = WATER_BANDS
+                    .iter()
+                    .rev()
+                    .find(|min| water >= min)
+                    .copied()
+                    .unwrap_or(0);
+                *self.jumps_by_water.entry(band).or_default() += 1;
+            }
+            GameEvent::Switch { .. } => self.switches += 1,
+            GameEvent::Offering {
+                npc, drops, score, ..
+            } => self
+                .offerings
+                .entry(npc.clone())
+                .or_default()
+                .push((*drops, *score)),
+            GameEvent::Reset => self.resets += 1,
+            GameEvent::GameOver { score } => self.final_scores.push(*score),
+        }
+    }
+}
+
+fn percent(part: u32, whole: u32) -> f32 {
+    if whole == 0 {
+        0.0
+    } else {
+        100.0 * part as f32 / whole as f32
+    }
+}
+
+fn mean(values: impl Iterator<Item = u32>) -> f32 {
+    let (sum, count) = values.fold((0, 0), |(sum, count), v| (sum + v, count + 1));
+    if count == 0 {
+        0.0
+    } else {
+        sum as f32 / count as f32
+    }
+}
+
+impl fmt::Display for TelemetrySummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "Games: {} started, {} finished, mean final score {:.1}",
+            self.games,
+            self.final_scores.len(),
+            mean(self.final_scores.iter().copied())
+        )?;
+        writeln!(
+            f,
+            "Drops: {} spawned, {} collected ({:.0}%), {} expired",
+            self.drops_spawned,
+            self.drops_collected,
+            percent(self.drops_collected, self.drops_spawned),
+            self.drops_expired
+        )?;
+        writeln!(f, "Drops by height (spawned / collected):")?;
+        for (band, (spawned, collected)) in self.drops_by_height.iter().rev() {
+            writeln!(
+                f,
+                "  {:>6} to {:>6}: {:>5} / {:>5}",
+                *band as f32 * HEIGHT_BAND,
+                (*band + 1) as f32 * HEIGHT_BAND,
+                spawned,
+                collected
+            )?;
+        }
+        writeln!(f, "Jumps by drops carried:")?;
+        for (water, jumps) in &self.jumps_by_water {
+            writeln!(f, "  {:>3}+: {}", water, jumps)?;
+        }
+        writeln!(f, "Character switches: {}", self.switches)?;
+        for (npc, offerings) in &self.offerings {
+            writeln!(
+                f,
+                "Offerings to {}: {}, mean {:.1} drops for {:.1} points",
+                npc,
+                offerings.len(),
+                mean(offerings.iter().map(|(drops, _)| *drops)),
+                mean(offerings.iter().map(|(_, score)| *score))
+            )?;
+        }
+        write!(f, "Resets: {}", self.resets)
+    }
+}
+
+/// Adds up every record in `paths`, skipping lines that do not parse.
+pub fn summarize(paths: &[String]) -> Result<TelemetrySummary, String> {
+    let mut summary = TelemetrySummary::default();
+    for path in paths {
+        let file = File::open(path).map_err(|e| format!("{}: {}", path, e))?;
+        for (number, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.map_err(|e| format!("{}: {}", path, e))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<TelemetryRecord>(&line) {
+                Ok(record) => summary.add(&record),
+                Err(e) => eprintln!("Skipping {}:{}: {}", path, number + 1, e),
+            }
+        }
+    }
+    Ok(summary)
+}
+
+/// Paths given after `--telemetry-summary`, if the game was started in that mode.
+pub fn summary_paths() -> Option<Vec<String>> {
+    let mut args = std::env::args().skip_while(|arg| arg != "--telemetry-summary");
+    args.next()?;
+    Some(args.collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::headless::{active_player, headless_app, run, spawn_drop, start_game, state};
+    use crate::settings::Settings;
+    use crate::AppState;
+
+    #[test]
+    fn telemetry_records_a_round() {
+        let path = std::env::temp_dir().join(format!("telemetry-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut app = headless_app();
+        app.insert_resource(TelemetryRecorder::new(path.display().to_string()));
+        app.world_mut().resource_mut::<Settings>().total_seconds = 1.0;
+        start_game(&mut app);
+
+        let player = active_player(&mut app);
+        let position = app.world().get::<Transform>(player).unwrap().translation;
+        spawn_drop(&mut app, position);
+        run(&mut app, 90);
+        assert_eq!(state(&app), AppState::GameOver);
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let records = contents
+            .lines()
+            .map(|line| serde_json::from_str::<TelemetryRecord>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert!(matches!(records[0].event, GameEvent::Start { seconds, .. } if seconds == 1.0));
+        assert!(matches!(
+            records.last().unwrap().event,
+            GameEvent::GameOver { score: 0 }
+        ));
+
+        let summary = summarize(&[path.display().to_string()]).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(summary.games, 1);
+        assert_eq!(summary.final_scores, [0]);
+        assert_eq!(summary.drops_collected, 1);
+    }
+}